@@ -0,0 +1 @@
+ALTER TABLE lyrics ADD COLUMN enhanced_lyrics TEXT;
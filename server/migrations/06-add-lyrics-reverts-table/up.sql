@@ -0,0 +1,16 @@
+CREATE TABLE lyrics_reverts (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  track_id INTEGER,
+  reverted_lyrics_id INTEGER,
+  restored_lyrics_id INTEGER,
+  lyrics_id INTEGER,
+  actor TEXT,
+  reason TEXT,
+  created_at DATETIME,
+  FOREIGN KEY (track_id) REFERENCES tracks (id),
+  FOREIGN KEY (reverted_lyrics_id) REFERENCES lyrics (id),
+  FOREIGN KEY (restored_lyrics_id) REFERENCES lyrics (id),
+  FOREIGN KEY (lyrics_id) REFERENCES lyrics (id)
+);
+
+CREATE INDEX idx_lyrics_reverts_track_id ON lyrics_reverts (track_id);
@@ -0,0 +1,32 @@
+-- Net votes of the revision, on top of the score a publish or revert starts with to rank above the
+-- revisions before it. Tracks serve their highest-scoring revision, the newest one on ties.
+ALTER TABLE lyrics ADD COLUMN score INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE lyrics_votes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  lyrics_id INTEGER,
+  value INTEGER,
+  created_at DATETIME,
+  FOREIGN KEY (lyrics_id) REFERENCES lyrics (id)
+);
+
+CREATE INDEX idx_lyrics_votes_lyrics_id ON lyrics_votes (lyrics_id);
+CREATE INDEX idx_lyrics_track_id_score ON lyrics (track_id, score DESC, id DESC);
+
+DROP TRIGGER set_tracks_last_lyrics_id;
+
+CREATE TRIGGER set_tracks_last_lyrics_id
+AFTER INSERT ON lyrics
+BEGIN
+  UPDATE tracks SET last_lyrics_id = (
+    SELECT id FROM lyrics WHERE lyrics.track_id = NEW.track_id ORDER BY score DESC, id DESC LIMIT 1
+  ) WHERE tracks.id = NEW.track_id;
+END;
+
+CREATE TRIGGER update_tracks_last_lyrics_id
+AFTER UPDATE OF score ON lyrics
+BEGIN
+  UPDATE tracks SET last_lyrics_id = (
+    SELECT id FROM lyrics WHERE lyrics.track_id = NEW.track_id ORDER BY score DESC, id DESC LIMIT 1
+  ) WHERE tracks.id = NEW.track_id;
+END;
@@ -0,0 +1,28 @@
+ALTER TABLE flags ADD COLUMN status TEXT NOT NULL DEFAULT 'open';
+ALTER TABLE flags ADD COLUMN reviewer TEXT;
+ALTER TABLE flags ADD COLUMN resolution_notes TEXT;
+ALTER TABLE flags ADD COLUMN resolved_at DATETIME;
+
+CREATE INDEX idx_flags_status_lyrics_id ON flags (status, lyrics_id);
+
+-- Hidden revisions are never served, tracks fall back to their best remaining revision
+ALTER TABLE lyrics ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT 0;
+
+DROP TRIGGER set_tracks_last_lyrics_id;
+DROP TRIGGER update_tracks_last_lyrics_id;
+
+CREATE TRIGGER set_tracks_last_lyrics_id
+AFTER INSERT ON lyrics
+BEGIN
+  UPDATE tracks SET last_lyrics_id = (
+    SELECT id FROM lyrics WHERE lyrics.track_id = NEW.track_id AND NOT lyrics.hidden ORDER BY score DESC, id DESC LIMIT 1
+  ) WHERE tracks.id = NEW.track_id;
+END;
+
+CREATE TRIGGER update_tracks_last_lyrics_id
+AFTER UPDATE OF score, hidden ON lyrics
+BEGIN
+  UPDATE tracks SET last_lyrics_id = (
+    SELECT id FROM lyrics WHERE lyrics.track_id = NEW.track_id AND NOT lyrics.hidden ORDER BY score DESC, id DESC LIMIT 1
+  ) WHERE tracks.id = NEW.track_id;
+END;
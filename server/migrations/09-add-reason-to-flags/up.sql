@@ -0,0 +1,8 @@
+ALTER TABLE flags ADD COLUMN reason TEXT NOT NULL DEFAULT 'other';
+ALTER TABLE flags ADD COLUMN details TEXT;
+ALTER TABLE flags ADD COLUMN suggested_offset INTEGER;
+
+-- Free-text flags filed before reasons existed
+UPDATE flags SET details = NULLIF(TRIM(content), '');
+
+CREATE INDEX idx_flags_reason ON flags (reason);
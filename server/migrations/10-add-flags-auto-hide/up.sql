@@ -0,0 +1,7 @@
+-- Challenge prefix of the publish token used to file the flag, to trace which challenge filed it
+ALTER TABLE flags ADD COLUMN token_prefix TEXT;
+
+-- Set when the revision was hidden by the flag threshold rather than by a moderator
+ALTER TABLE lyrics ADD COLUMN auto_hidden BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_flags_lyrics_id_created_at ON flags (lyrics_id, created_at);
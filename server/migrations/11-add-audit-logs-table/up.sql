@@ -0,0 +1,29 @@
+-- No foreign keys, the audit trail has to outlive deleted tracks and lyrics
+CREATE TABLE audit_logs (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  actor TEXT NOT NULL,
+  action TEXT NOT NULL,
+  track_id INTEGER,
+  lyrics_id INTEGER,
+  before TEXT,
+  after TEXT,
+  created_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_audit_logs_actor ON audit_logs (actor);
+CREATE INDEX idx_audit_logs_action ON audit_logs (action);
+CREATE INDEX idx_audit_logs_track_id ON audit_logs (track_id);
+CREATE INDEX idx_audit_logs_lyrics_id ON audit_logs (lyrics_id);
+CREATE INDEX idx_audit_logs_created_at ON audit_logs (created_at);
+
+CREATE TRIGGER audit_logs_no_update
+BEFORE UPDATE ON audit_logs
+BEGIN
+  SELECT RAISE(ABORT, 'audit_logs is append-only');
+END;
+
+CREATE TRIGGER audit_logs_no_delete
+BEFORE DELETE ON audit_logs
+BEGIN
+  SELECT RAISE(ABORT, 'audit_logs is append-only');
+END;
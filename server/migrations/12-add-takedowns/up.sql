@@ -0,0 +1,19 @@
+-- Legal takedowns of a whole track (lyrics_id is NULL) or of a single lyrics revision.
+-- Rows are never deleted, lifting a takedown only sets lifted_at.
+CREATE TABLE takedowns (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  track_id INTEGER NOT NULL,
+  lyrics_id INTEGER,
+  reason TEXT NOT NULL,
+  actor TEXT NOT NULL,
+  created_at DATETIME NOT NULL,
+  lifted_at DATETIME,
+  lifted_by TEXT,
+  FOREIGN KEY (track_id) REFERENCES tracks (id),
+  FOREIGN KEY (lyrics_id) REFERENCES lyrics (id)
+);
+
+CREATE INDEX idx_takedowns_track_id ON takedowns (track_id);
+
+ALTER TABLE tracks ADD COLUMN taken_down BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE lyrics ADD COLUMN taken_down BOOLEAN NOT NULL DEFAULT 0;
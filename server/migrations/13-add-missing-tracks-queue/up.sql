@@ -0,0 +1,8 @@
+-- missing_tracks becomes the queue of tracks to look up with the lyrics providers.
+-- A worker claims a row, then deletes it when done or releases it to be retried later.
+ALTER TABLE missing_tracks ADD COLUMN claimed_at DATETIME;
+ALTER TABLE missing_tracks ADD COLUMN claimed_by TEXT;
+ALTER TABLE missing_tracks ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE missing_tracks ADD COLUMN available_at DATETIME;
+
+CREATE INDEX idx_missing_tracks_claimed_at ON missing_tracks (claimed_at);
@@ -0,0 +1,127 @@
+use serde::Deserialize;
+use crate::{
+  lrc::{self, Line, Lrc},
+  subtitles::{self, ParseError},
+};
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+  Lrc,
+  EnhancedLrc,
+  Srt,
+  Vtt,
+  Ttml,
+  Plain,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetFormat {
+  Lrc,
+  EnhancedLrc,
+  Srt,
+  Vtt,
+  Ttml,
+  Ass,
+  Plain,
+}
+
+impl SourceFormat {
+  pub fn is_lrc(&self) -> bool {
+    matches!(self, SourceFormat::Lrc | SourceFormat::EnhancedLrc)
+  }
+}
+
+impl TargetFormat {
+  pub fn needs_timestamps(&self) -> bool {
+    !matches!(self, TargetFormat::Plain)
+  }
+}
+
+pub fn parse(input: &str, format: SourceFormat) -> Result<Lrc, ParseError> {
+  let cues = match format {
+    SourceFormat::Lrc | SourceFormat::EnhancedLrc => return Ok(lrc::parse(input)),
+    SourceFormat::Plain => return Ok(parse_plain(input)),
+    SourceFormat::Srt => subtitles::srt::parse(input)?,
+    SourceFormat::Vtt => subtitles::vtt::parse(input)?,
+    SourceFormat::Ttml => subtitles::ttml::parse(input)?,
+  };
+  Ok(subtitles::to_lrc(&cues))
+}
+
+// Every non-empty line becomes an untimed line, brackets are taken as part of the text
+fn parse_plain(input: &str) -> Lrc {
+  let lines = input
+    .lines()
+    .enumerate()
+    .filter(|(_, raw_line)| !raw_line.trim().is_empty())
+    .map(|(index, raw_line)| Line {
+      line_number: index + 1,
+      timestamps: vec![],
+      text: raw_line.trim().to_owned(),
+      voice: None,
+      words: vec![],
+    })
+    .collect();
+  Lrc { lines, ..Lrc::default() }
+}
+
+// Duration is only used to end the last cue of subtitle formats
+pub fn render(lrc: &Lrc, format: TargetFormat, duration: Option<f64>) -> String {
+  let subtitle_format = match format {
+    TargetFormat::Lrc => return lrc.to_line_level().to_string(),
+    TargetFormat::EnhancedLrc => return lrc.to_string(),
+    TargetFormat::Plain => return lrc.to_plain(),
+    TargetFormat::Srt => subtitles::Format::Srt,
+    TargetFormat::Vtt => subtitles::Format::Vtt,
+    TargetFormat::Ttml => subtitles::Format::Ttml,
+    TargetFormat::Ass => subtitles::Format::Ass,
+  };
+  subtitles::render(subtitle_format, &subtitles::cues(lrc, duration))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn plain_source_has_untimed_lines() {
+    let lrc = parse("First line\n\n[Chorus]\n", SourceFormat::Plain).unwrap();
+    assert!(!lrc.has_timestamps());
+    assert!(lrc.tags.is_empty());
+    assert_eq!(lrc.lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>(), ["First line", "[Chorus]"]);
+    assert_eq!(render(&lrc, TargetFormat::Plain, None), "First line\n[Chorus]");
+  }
+
+  #[test]
+  fn srt_converts_to_lrc_with_gaps_kept() {
+    let lrc = parse("1\n00:00:01,000 --> 00:00:02,000\nHello\n\n2\n00:00:03,000 --> 00:00:04,500\nWorld\n", SourceFormat::Srt).unwrap();
+    assert_eq!(render(&lrc, TargetFormat::Lrc, None), "[00:01.00]Hello\n[00:02.00]\n[00:03.00]World\n[00:04.50]");
+    assert_eq!(render(&lrc, TargetFormat::Plain, None), "Hello\n\nWorld");
+  }
+
+  #[test]
+  fn enhanced_lrc_keeps_or_drops_word_timings() {
+    let lrc = parse("[00:01.00]<00:01.00>Hello <00:01.50>world", SourceFormat::EnhancedLrc).unwrap();
+    assert_eq!(render(&lrc, TargetFormat::EnhancedLrc, None), "[00:01.00]<00:01.00>Hello <00:01.50>world");
+    assert_eq!(render(&lrc, TargetFormat::Lrc, None), "[00:01.00]Hello world");
+    assert_eq!(
+      render(&lrc, TargetFormat::Vtt, Some(3.0)),
+      "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\nHello <00:00:01.500>world\n",
+    );
+  }
+
+  #[test]
+  fn malformed_subtitles_are_an_error() {
+    let err = parse("Hello\n", SourceFormat::Vtt).unwrap_err();
+    assert_eq!(err.line_number, 1);
+  }
+
+  #[test]
+  fn only_plain_target_works_without_timestamps() {
+    assert!(!TargetFormat::Plain.needs_timestamps());
+    assert!(TargetFormat::Lrc.needs_timestamps());
+    assert!(TargetFormat::Srt.needs_timestamps());
+  }
+}
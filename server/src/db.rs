@@ -40,3 +40,12 @@ pub fn migrate(conn: &mut Connection) -> Result<()> {
   MIGRATIONS.to_latest(conn)?;
   Ok(())
 }
+
+// Migrated in-memory database for unit tests
+#[cfg(test)]
+pub fn test_connection() -> Connection {
+  let mut conn = Connection::open_in_memory().unwrap();
+  conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+  migrate(&mut conn).unwrap();
+  conn
+}
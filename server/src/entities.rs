@@ -1,3 +1,6 @@
 pub mod track;
 pub mod lyrics;
 pub mod missing_track;
+pub mod flag;
+pub mod audit_log;
+pub mod takedown;
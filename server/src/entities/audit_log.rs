@@ -0,0 +1,59 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+  RevertLyrics,
+  HideLyrics,
+  UnhideLyrics,
+  AutoHideLyrics,
+  DeleteLyrics,
+  UpdateTrack,
+  MergeTracks,
+  ResolveFlags,
+  PurgeCache,
+  TakeDown,
+  LiftTakedown,
+}
+
+impl AuditAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditAction::RevertLyrics => "revert_lyrics",
+      AuditAction::HideLyrics => "hide_lyrics",
+      AuditAction::UnhideLyrics => "unhide_lyrics",
+      AuditAction::AutoHideLyrics => "auto_hide_lyrics",
+      AuditAction::DeleteLyrics => "delete_lyrics",
+      AuditAction::UpdateTrack => "update_track",
+      AuditAction::MergeTracks => "merge_tracks",
+      AuditAction::ResolveFlags => "resolve_flags",
+      AuditAction::PurgeCache => "purge_cache",
+      AuditAction::TakeDown => "take_down",
+      AuditAction::LiftTakedown => "lift_takedown",
+    }
+  }
+}
+
+// Actor recorded for changes made by the server itself, e.g. the flag threshold
+pub const SYSTEM_ACTOR: &str = "system";
+
+pub struct AuditLog {
+  pub id: i64,
+  pub actor: String,
+  pub action: String,
+  pub track_id: Option<i64>,
+  pub lyrics_id: Option<i64>,
+  pub before: Option<serde_json::Value>,
+  pub after: Option<serde_json::Value>,
+  pub created_at: DateTime<Utc>,
+}
+
+pub struct AuditLogFilter {
+  pub actor: Option<String>,
+  pub action: Option<AuditAction>,
+  pub track_id: Option<i64>,
+  pub lyrics_id: Option<i64>,
+  pub since: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+}
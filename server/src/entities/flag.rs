@@ -0,0 +1,110 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagStatus {
+  Open,
+  Accepted,
+  Rejected,
+}
+
+impl FlagStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      FlagStatus::Open => "open",
+      FlagStatus::Accepted => "accepted",
+      FlagStatus::Rejected => "rejected",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    match value {
+      "open" => Some(FlagStatus::Open),
+      "accepted" => Some(FlagStatus::Accepted),
+      "rejected" => Some(FlagStatus::Rejected),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagReason {
+  WrongSong,
+  OutOfSync,
+  Incomplete,
+  Spam,
+  Offensive,
+  Copyright,
+  WrongInstrumental,
+  // Also used for free-text flags filed before reasons existed
+  Other,
+}
+
+impl FlagReason {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      FlagReason::WrongSong => "wrong_song",
+      FlagReason::OutOfSync => "out_of_sync",
+      FlagReason::Incomplete => "incomplete",
+      FlagReason::Spam => "spam",
+      FlagReason::Offensive => "offensive",
+      FlagReason::Copyright => "copyright",
+      FlagReason::WrongInstrumental => "wrong_instrumental",
+      FlagReason::Other => "other",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    match value {
+      "wrong_song" => Some(FlagReason::WrongSong),
+      "out_of_sync" => Some(FlagReason::OutOfSync),
+      "incomplete" => Some(FlagReason::Incomplete),
+      "spam" => Some(FlagReason::Spam),
+      "offensive" => Some(FlagReason::Offensive),
+      "copyright" => Some(FlagReason::Copyright),
+      "wrong_instrumental" => Some(FlagReason::WrongInstrumental),
+      "other" => Some(FlagReason::Other),
+      _ => None,
+    }
+  }
+}
+
+// Hide a lyrics revision once it has collected `threshold` open flags within `window`,
+// until a moderator reviews the flags. Each flag takes a fresh publish token.
+#[derive(Debug, Clone, Copy)]
+pub struct AutoHidePolicy {
+  pub threshold: i64,
+  pub window: chrono::Duration,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Flag {
+  pub id: i64,
+  pub lyrics_id: Option<i64>,
+  pub reason: FlagReason,
+  pub details: Option<String>,
+  // Milliseconds, with the same meaning as the LRC [offset:] tag
+  pub suggested_offset: Option<i64>,
+  pub status: FlagStatus,
+  pub reviewer: Option<String>,
+  pub resolution_notes: Option<String>,
+  pub created_at: Option<DateTime<Utc>>,
+  pub resolved_at: Option<DateTime<Utc>>,
+}
+
+// Flags of one lyrics revision, with the track they were filed against
+pub struct FlagGroup {
+  pub lyrics_id: i64,
+  pub track_id: i64,
+  pub track_name: Option<String>,
+  pub artist_name: Option<String>,
+  pub album_name: Option<String>,
+  pub hidden: bool,
+  pub auto_hidden: bool,
+  pub flags_count: i64,
+  pub first_flagged_at: Option<DateTime<Utc>>,
+  pub last_flagged_at: Option<DateTime<Utc>>,
+}
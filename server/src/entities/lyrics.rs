@@ -1,13 +1,22 @@
 use chrono::prelude::*;
+use serde::Serialize;
+use crate::lrc;
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Lyrics {
   pub id: i64,
   pub plain_lyrics: Option<String>,
   pub synced_lyrics: Option<String>,
+  pub enhanced_lyrics: Option<String>,
   pub track_id: i64,
   pub has_plain_lyrics: bool,
   pub has_synced_lyrics: bool,
   pub instrumental: bool,
+  pub score: i64,
+  pub hidden: bool,
+  pub auto_hidden: bool,
+  pub taken_down: bool,
   pub source: Option<String>,
   pub created_at: Option<DateTime<Utc>>,
   pub updated_at: Option<DateTime<Utc>>,
@@ -16,5 +25,17 @@ pub struct Lyrics {
 pub struct SimpleLyrics {
   pub plain_lyrics: Option<String>,
   pub synced_lyrics: Option<String>,
+  pub enhanced_lyrics: Option<String>,
   pub instrumental: bool,
 }
+
+impl SimpleLyrics {
+  // Shift synced lyrics as if they had an [offset:] tag of offset_ms
+  pub fn with_offset(self, offset_ms: i64) -> Self {
+    Self {
+      synced_lyrics: self.synced_lyrics.map(|lyrics| lrc::apply_offset(&lyrics, offset_ms)),
+      enhanced_lyrics: self.enhanced_lyrics.map(|lyrics| lrc::apply_offset(&lyrics, offset_ms)),
+      ..self
+    }
+  }
+}
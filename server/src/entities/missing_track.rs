@@ -36,3 +36,19 @@ impl PartialEq for MissingTrack {
 }
 
 impl Eq for MissingTrack {}
+
+// A missing track claimed from the queue by a worker
+pub struct QueuedMissingTrack {
+  pub id: i64,
+  // Including the current one
+  pub attempts: i64,
+  pub track: MissingTrack,
+}
+
+pub struct MissingTrackCounts {
+  // Waiting for a worker, including tracks to be retried later
+  pub pending_count: usize,
+  pub claimed_count: usize,
+  // Used up their attempts, kept until cleaned up
+  pub failed_count: usize,
+}
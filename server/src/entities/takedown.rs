@@ -0,0 +1,16 @@
+use chrono::prelude::*;
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Takedown {
+  pub id: i64,
+  pub track_id: i64,
+  // None when the whole track is taken down
+  pub lyrics_id: Option<i64>,
+  pub reason: String,
+  pub actor: String,
+  pub created_at: DateTime<Utc>,
+  pub lifted_at: Option<DateTime<Utc>>,
+  pub lifted_by: Option<String>,
+}
@@ -1,6 +1,9 @@
 use super::lyrics::SimpleLyrics;
 use chrono::prelude::*;
+use serde::Serialize;
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Track {
   pub id: i64,
   pub name: Option<String>,
@@ -8,6 +11,8 @@ pub struct Track {
   pub artist_name: Option<String>,
   pub duration: Option<f64>,
   pub last_lyrics_id: Option<i64>,
+  pub taken_down: bool,
+  #[serde(skip)]
   pub last_lyrics: Option<SimpleLyrics>,
   pub created_at: Option<DateTime<Utc>>,
   pub updated_at: Option<DateTime<Utc>>,
@@ -19,5 +24,6 @@ pub struct SimpleTrack {
   pub album_name: Option<String>,
   pub artist_name: Option<String>,
   pub duration: Option<f64>,
+  pub taken_down: bool,
   pub last_lyrics: Option<SimpleLyrics>,
 }
@@ -4,6 +4,7 @@ use axum::{
   Json,
 };
 use serde::Serialize;
+use crate::lrc::lint::LintIssue;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,10 +14,25 @@ pub struct ApiErrorResponse {
   status_code: u16
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsValidationErrorResponse {
+  message: String,
+  name: String,
+  status_code: u16,
+  errors: Vec<LintIssue>,
+}
+
 pub enum ApiError {
   TrackNotFoundError,
+  SyncedLyricsNotFoundError,
+  LyricsNotFoundError,
   IncorrectPublishTokenError,
+  UnauthorizedError,
+  TakenDownError,
+  ConflictError(String),
   ValidationError(String),
+  LyricsValidationError(Vec<LintIssue>),
   UnknownError(anyhow::Error),
 }
 
@@ -33,6 +49,26 @@ impl IntoResponse for ApiError {
             }
           )
         ).into_response(),
+      ApiError::SyncedLyricsNotFoundError => (
+        StatusCode::NOT_FOUND,
+        Json(
+          ApiErrorResponse {
+            message: "The specified track does not have synced lyrics".to_owned(),
+            name: "SyncedLyricsNotFound".to_owned(),
+            status_code: StatusCode::NOT_FOUND.as_u16(),
+          }
+        )
+      ).into_response(),
+      ApiError::LyricsNotFoundError => (
+        StatusCode::NOT_FOUND,
+        Json(
+          ApiErrorResponse {
+            message: "Failed to find specified lyrics".to_owned(),
+            name: "LyricsNotFound".to_owned(),
+            status_code: StatusCode::NOT_FOUND.as_u16(),
+          }
+        )
+      ).into_response(),
       ApiError::IncorrectPublishTokenError => (
         StatusCode::BAD_REQUEST,
         Json(
@@ -43,6 +79,34 @@ impl IntoResponse for ApiError {
           }
         )
       ).into_response(),
+      ApiError::UnauthorizedError => (
+        StatusCode::UNAUTHORIZED,
+        Json(
+          ApiErrorResponse {
+            message: "The provided admin token is incorrect".to_owned(),
+            name: "Unauthorized".to_owned(),
+            status_code: StatusCode::UNAUTHORIZED.as_u16(),
+          }
+        )
+      ).into_response(),
+      ApiError::TakenDownError => (
+        StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+        Json(
+          ApiErrorResponse {
+            message: "The requested content has been taken down".to_owned(),
+            name: "UnavailableForLegalReasons".to_owned(),
+            status_code: StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS.as_u16(),
+          }
+        )
+      ).into_response(),
+      ApiError::ConflictError(err_msg) => (
+        StatusCode::CONFLICT,
+        Json(ApiErrorResponse {
+          message: err_msg,
+          name: "Conflict".to_owned(),
+          status_code: StatusCode::CONFLICT.as_u16(),
+        }),
+      ).into_response(),
       ApiError::ValidationError(err_msg) => (
         StatusCode::BAD_REQUEST,
         Json(ApiErrorResponse {
@@ -51,6 +115,15 @@ impl IntoResponse for ApiError {
           status_code: StatusCode::BAD_REQUEST.as_u16(),
         }),
       ).into_response(),
+      ApiError::LyricsValidationError(errors) => (
+        StatusCode::BAD_REQUEST,
+        Json(LyricsValidationErrorResponse {
+          message: errors.iter().map(|issue| issue.to_string()).collect::<Vec<String>>().join("; "),
+          name: "ValidationError".to_owned(),
+          status_code: StatusCode::BAD_REQUEST.as_u16(),
+          errors,
+        }),
+      ).into_response(),
       ApiError::UnknownError(err) => {
         tracing::error!(message = "unknown error happened", error = err.to_string());
         (
@@ -5,11 +5,12 @@ use axum::{
   },
   body::Body,
   response::Response,
-  routing::{get, post},
+  routing::{delete, get, patch, post},
   Router,
 };
-use entities::missing_track::MissingTrack;
-use repositories::lyrics_repository::get_last_10_mins_lyrics_count;
+use entities::flag::AutoHidePolicy;
+use providers::{build_providers, LyricsProvider, ProvidersConfig};
+use repositories::{lyrics_repository::get_last_10_mins_lyrics_count, missing_track_repository::clean_old_missing_tracks};
 use tracing_subscriber::EnvFilter;
 use std::{path::PathBuf, time::Duration};
 use r2d2::Pool;
@@ -21,6 +22,13 @@ use routes::{
   request_challenge,
   publish_lyrics,
   flag_lyrics,
+  validate_lyrics,
+  convert_lyrics,
+  get_lyrics_history,
+  get_lyrics_by_id,
+  get_lyrics_diff,
+  admin,
+  vote_lyrics,
 };
 use std::sync::Arc;
 use db::init_db;
@@ -32,7 +40,6 @@ use moka::future::Cache;
 use tokio::signal;
 use queue::start_queue;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use crossbeam_queue::ArrayQueue;
 
 pub mod errors;
 pub mod routes;
@@ -42,24 +49,65 @@ pub mod utils;
 pub mod db;
 pub mod queue;
 pub mod providers;
+pub mod lrc;
+pub mod subtitles;
+pub mod convert;
 
 pub struct AppState {
   pool: Pool<SqliteConnectionManager>,
   challenge_cache: Cache<String, String>,
   get_cache: Cache<String, String>,
   search_cache: Cache<String, String>,
-  queue: ArrayQueue<MissingTrack>,
   request_counter: AtomicUsize,
   recent_lyrics_count: AtomicUsize,
+  admin_token: Option<String>,
+  auto_hide_policy: Option<AutoHidePolicy>,
+  providers: Vec<Box<dyn LyricsProvider>>,
 }
 
-pub async fn serve(port: u16, database: &PathBuf, workers_count: u8) {
+// State over a migrated in-memory database for handler tests. The pool holds a single
+// connection, since every in-memory connection would otherwise open its own database.
+#[cfg(test)]
+pub fn test_state(admin_token: Option<&str>) -> Arc<AppState> {
+  let pool = r2d2::Pool::builder()
+    .max_size(1)
+    .idle_timeout(None)
+    .max_lifetime(None)
+    .build(SqliteConnectionManager::memory())
+    .unwrap();
+  db::migrate(&mut pool.get().unwrap()).unwrap();
+
+  Arc::new(
+    AppState {
+      pool,
+      challenge_cache: Cache::builder().build(),
+      get_cache: Cache::builder().build(),
+      search_cache: Cache::builder().support_invalidation_closures().build(),
+      request_counter: AtomicUsize::new(0),
+      recent_lyrics_count: AtomicUsize::new(0),
+      admin_token: admin_token.map(|admin_token| admin_token.to_owned()),
+      auto_hide_policy: None,
+      providers: vec![],
+    }
+  )
+}
+
+pub async fn serve(
+  port: u16,
+  database: &PathBuf,
+  workers_count: u8,
+  admin_token: Option<String>,
+  flag_threshold: u32,
+  flag_window_hours: u32,
+  providers_config: ProvidersConfig,
+) {
   tracing_subscriber::fmt()
     .compact()
     .with_env_filter(EnvFilter::from_env("LRCLIB_LOG"))
     .init();
 
   let pool = init_db(database).expect("Cannot initialize connection to SQLite database!");
+  let providers = build_providers(&providers_config).expect("Cannot initialize lyrics providers!");
 
   let state = Arc::new(
     AppState {
@@ -76,10 +124,16 @@ pub async fn serve(port: u16, database: &PathBuf, workers_count: u8) {
         .time_to_live(Duration::from_secs(60 * 60 * 24))
         .time_to_idle(Duration::from_secs(60 * 60 * 4))
         .max_capacity(400000)
+        .support_invalidation_closures()
         .build(),
-      queue: ArrayQueue::new(600000),
       request_counter: AtomicUsize::new(0),
       recent_lyrics_count: AtomicUsize::new(0),
+      admin_token: admin_token.filter(|admin_token| !admin_token.is_empty()),
+      auto_hide_policy: (flag_threshold > 0).then(|| AutoHidePolicy {
+        threshold: flag_threshold.into(),
+        window: chrono::Duration::hours(flag_window_hours.into()),
+      }),
+      providers,
     }
   );
 
@@ -87,14 +141,34 @@ pub async fn serve(port: u16, database: &PathBuf, workers_count: u8) {
   let state_for_metrics = state.clone();
   let state_for_recent_lyrics_count = state.clone();
   let state_for_queue = state.clone();
+  let state_for_queue_cleanup = state.clone();
 
   let api_routes = Router::new()
     .route("/get", get(get_lyrics_by_metadata::route))
     .route("/get/:track_id", get(get_lyrics_by_track_id::route))
+    .route("/get/:track_id/history", get(get_lyrics_history::route))
+    .route("/get/:track_id/diff", get(get_lyrics_diff::route))
+    .route("/lyrics/:lyrics_id", get(get_lyrics_by_id::route))
     .route("/search", get(search_lyrics::route))
     .route("/request-challenge", post(request_challenge::route))
     .route("/publish", post(publish_lyrics::route))
-    .route("/flag", post(flag_lyrics::route));
+    .route("/flag", post(flag_lyrics::route))
+    .route("/vote", post(vote_lyrics::route))
+    .route("/validate", post(validate_lyrics::route))
+    .route("/convert", post(convert_lyrics::route));
+
+  let admin_routes = Router::new()
+    .route("/tracks/:track_id", patch(admin::update_track::route))
+    .route("/tracks/:track_id/revert", post(admin::revert_lyrics::route))
+    .route("/tracks/:track_id/merge", post(admin::merge_tracks::route))
+    .route("/lyrics/:lyrics_id", patch(admin::update_lyrics::route).delete(admin::delete_lyrics::route))
+    .route("/lyrics/:lyrics_id/flags/resolve", post(admin::resolve_flags::route))
+    .route("/flags", get(admin::list_flags::route))
+    .route("/cache/purge", post(admin::purge_cache::route))
+    .route("/queue", get(admin::get_queue::route))
+    .route("/audit-logs", get(admin::list_audit_logs::route))
+    .route("/takedowns", get(admin::list_takedowns::route).post(admin::create_takedown::route))
+    .route("/takedowns/:takedown_id", delete(admin::lift_takedown::route));
 
   // Metrics
   tokio::spawn(async move {
@@ -119,8 +193,27 @@ pub async fn serve(port: u16, database: &PathBuf, workers_count: u8) {
     }
   });
 
-  let app = Router::new()
-    .nest("/api", api_routes)
+  // Old failed missing tracks
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+    loop {
+      interval.tick().await;
+      let mut conn = state_for_queue_cleanup.pool.get().unwrap();
+      match clean_old_missing_tracks(queue::MAX_ATTEMPTS, &mut conn) {
+        Ok(count) => tracing::info!(message = "cleaned old missing tracks", deleted_count = count),
+        Err(err) => tracing::error!(message = "failed to clean old missing tracks", error = err.to_string()),
+      }
+    }
+  });
+
+  // The admin API is only mounted when an admin token is configured
+  let mut app = Router::new()
+    .nest("/api", api_routes);
+  if state.admin_token.is_some() {
+    app = app.nest("/admin", admin_routes);
+  }
+
+  let app = app
     .with_state(state)
     .layer(
       TraceLayer::new_for_http()
@@ -168,7 +261,9 @@ pub async fn serve(port: u16, database: &PathBuf, workers_count: u8) {
         .allow_headers([
           header::CONTENT_TYPE,
           "X-User-Agent".parse().unwrap(),
-          "Lrclib-Client".parse().unwrap()
+          "Lrclib-Client".parse().unwrap(),
+          header::AUTHORIZATION,
+          "X-Admin-Actor".parse().unwrap()
         ])
     );
 
@@ -0,0 +1,536 @@
+use std::fmt;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+pub mod lint;
+pub mod diff;
+
+lazy_static! {
+  static ref PRECISE_TIMESTAMP_RE: Regex = Regex::new(r"\d+:\d{1,2}[.:]\d{3}\b").unwrap();
+  static ref TIMESTAMP_RE: Regex = Regex::new(r"^(\d+):(\d{1,2})(?:[.:](\d{1,3}))?$").unwrap();
+  static ref MALFORMED_TIMESTAMP_RE: Regex = Regex::new(r"^\s*-?\d\S*[:.]").unwrap();
+  static ref TAG_RE: Regex = Regex::new(r"^([A-Za-z#][A-Za-z0-9_#-]*):(.*)$").unwrap();
+  static ref VOICE_RE: Regex = Regex::new(r"^(v\d+):\s*").unwrap();
+  static ref WORD_TIMESTAMP_RE: Regex = Regex::new(r"<(\d+:\d{1,2}(?:[.:]\d{1,3})?)>").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+  pub line_number: usize,
+  pub key: String,
+  pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+  // 1-based line number in the source text
+  pub line_number: usize,
+  // Timestamps in milliseconds, as written in the source (without offset applied).
+  // Empty for lines that have no timestamp at all.
+  pub timestamps: Vec<i64>,
+  // Text without voice marker and word timestamps
+  pub text: String,
+  // Enhanced LRC voice marker, e.g. "v1" for a "v1:" prefix in duets
+  pub voice: Option<String>,
+  // Enhanced LRC word timings. Empty for line-level lines.
+  pub words: Vec<Word>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+  // Milliseconds, as written in the source (without offset applied)
+  pub start_ms: i64,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+  MalformedTimestamp(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+  pub line_number: usize,
+  pub kind: ParseErrorKind,
+  // The line as written, so that it can be kept when the lyrics are written back
+  pub raw_line: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lrc {
+  pub tags: Vec<Tag>,
+  pub lines: Vec<Line>,
+  pub errors: Vec<ParseError>,
+  // Whether the source wrote timestamps with milliseconds rather than centiseconds
+  pub millisecond_precision: bool,
+}
+
+// A line expanded for one of its timestamps, with the offset already applied
+#[derive(Debug, Clone)]
+pub struct TimedLine<'a> {
+  pub start_ms: i64,
+  pub line: &'a Line,
+}
+
+pub fn parse(input: &str) -> Lrc {
+  let mut lrc = Lrc {
+    millisecond_precision: PRECISE_TIMESTAMP_RE.is_match(input),
+    ..Lrc::default()
+  };
+
+  'lines: for (index, raw_line) in input.lines().enumerate() {
+    let line_number = index + 1;
+    let mut rest = raw_line.trim();
+
+    if rest.is_empty() {
+      continue;
+    }
+
+    let mut timestamps = vec![];
+
+    while rest.starts_with('[') {
+      let Some(end) = rest.find(']') else {
+        break;
+      };
+      let inner = &rest[1..end];
+      let after = &rest[end + 1..];
+
+      if let Some(timestamp) = parse_timestamp(inner) {
+        timestamps.push(timestamp);
+        rest = after;
+        continue;
+      }
+
+      if timestamps.is_empty() && after.trim().is_empty() {
+        if let Some(captures) = TAG_RE.captures(inner) {
+          lrc.tags.push(Tag {
+            line_number,
+            key: captures[1].trim().to_owned(),
+            value: captures[2].trim().to_owned(),
+          });
+          continue 'lines;
+        }
+      }
+
+      if MALFORMED_TIMESTAMP_RE.is_match(inner) {
+        lrc.errors.push(ParseError {
+          line_number,
+          kind: ParseErrorKind::MalformedTimestamp(inner.to_owned()),
+          raw_line: raw_line.to_owned(),
+        });
+        continue 'lines;
+      }
+
+      break;
+    }
+
+    let mut voice = None;
+    if let Some(captures) = VOICE_RE.captures(rest) {
+      voice = Some(captures[1].to_owned());
+      rest = &rest[captures[0].len()..];
+    }
+
+    let words = parse_words(rest, timestamps.first().copied().unwrap_or(0));
+    let text = if words.is_empty() {
+      rest.trim().to_owned()
+    } else {
+      words.iter().map(|word| word.text.as_str()).collect::<String>().trim().to_owned()
+    };
+
+    lrc.lines.push(Line {
+      line_number,
+      timestamps,
+      text,
+      voice,
+      words,
+    });
+  }
+
+  lrc
+}
+
+// Split "<00:12.34>Hello <00:12.80>world<00:13.20>" into timed words. Text before
+// the first word timestamp starts with the line.
+fn parse_words(text: &str, line_start_ms: i64) -> Vec<Word> {
+  let mut words = vec![];
+  let mut start_ms = line_start_ms;
+  let mut last_end = 0;
+
+  for captures in WORD_TIMESTAMP_RE.captures_iter(text) {
+    let Some(timestamp) = parse_timestamp(&captures[1]) else {
+      continue;
+    };
+    let tag = captures.get(0).unwrap();
+    let segment = &text[last_end..tag.start()];
+
+    if last_end > 0 || !segment.trim().is_empty() {
+      words.push(Word { start_ms, text: segment.to_owned() });
+    }
+
+    start_ms = timestamp;
+    last_end = tag.end();
+  }
+
+  if last_end > 0 {
+    words.push(Word { start_ms, text: text[last_end..].to_owned() });
+  }
+
+  words
+}
+
+// Re-time LRC text as if it had an [offset:] tag of offset_ms on top of its own
+pub fn apply_offset(input: &str, offset_ms: i64) -> String {
+  let mut lrc = parse(input);
+  lrc.bake_offset();
+  lrc.shift(offset_ms.saturating_neg());
+  lrc.to_string()
+}
+
+pub fn parse_timestamp(input: &str) -> Option<i64> {
+  let captures = TIMESTAMP_RE.captures(input.trim())?;
+  let minutes: i64 = captures[1].parse().ok()?;
+  let seconds: i64 = captures[2].parse().ok()?;
+
+  if seconds >= 60 {
+    return None;
+  }
+
+  let millis: i64 = match captures.get(3) {
+    Some(fraction) => {
+      let fraction = fraction.as_str();
+      let value: i64 = fraction.parse().ok()?;
+      value * 10_i64.pow(3 - fraction.len() as u32)
+    },
+    None => 0,
+  };
+
+  // Absurdly large minutes are malformed rather than wrapped around
+  minutes.checked_mul(60_000)?.checked_add(seconds * 1000 + millis)
+}
+
+pub fn format_timestamp(ms: i64) -> String {
+  let centis = ms.max(0).saturating_add(5) / 10;
+  format!("{:02}:{:02}.{:02}", centis / 6000, centis / 100 % 60, centis % 100)
+}
+
+pub fn format_precise_timestamp(ms: i64) -> String {
+  let ms = ms.max(0);
+  format!("{:02}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
+
+impl Lrc {
+  pub fn remove_tags(&mut self, keys: &[&str]) {
+    self.tags.retain(|tag| !keys.iter().any(|key| tag.key.eq_ignore_ascii_case(key)));
+  }
+
+  pub fn tag(&self, key: &str) -> Option<&str> {
+    self.tags
+      .iter()
+      .find(|tag| tag.key.eq_ignore_ascii_case(key))
+      .map(|tag| tag.value.as_str())
+  }
+
+  // Value of the [offset:] tag in milliseconds. A positive offset makes lines appear sooner.
+  pub fn offset(&self) -> i64 {
+    self.tag("offset")
+      .and_then(|value| value.trim().parse::<i64>().ok())
+      .unwrap_or(0)
+  }
+
+  pub fn is_instrumental(&self) -> bool {
+    self.tag("au").is_some_and(|value| value.eq_ignore_ascii_case("instrumental"))
+  }
+
+  pub fn is_enhanced(&self) -> bool {
+    self.lines.iter().any(|line| line.voice.is_some() || !line.words.is_empty())
+  }
+
+  // Downgrade enhanced LRC to plain line-level LRC by dropping voice markers and word timings
+  pub fn to_line_level(&self) -> Lrc {
+    let mut lrc = self.clone();
+    for line in &mut lrc.lines {
+      line.voice = None;
+      line.words.clear();
+    }
+    lrc
+  }
+
+  // Move every line and word timestamp by delta_ms, never going below zero
+  pub fn shift(&mut self, delta_ms: i64) {
+    for line in &mut self.lines {
+      for timestamp in &mut line.timestamps {
+        *timestamp = timestamp.saturating_add(delta_ms).max(0);
+      }
+      for word in &mut line.words {
+        word.start_ms = word.start_ms.saturating_add(delta_ms).max(0);
+      }
+    }
+  }
+
+  // Multiply every line and word timestamp by factor, e.g. for a sped up or slowed down track
+  pub fn scale(&mut self, factor: f64) {
+    let scale = |timestamp: i64| (timestamp as f64 * factor).round() as i64;
+    for line in &mut self.lines {
+      for timestamp in &mut line.timestamps {
+        *timestamp = scale(*timestamp);
+      }
+      for word in &mut line.words {
+        word.start_ms = scale(word.start_ms);
+      }
+    }
+  }
+
+  // Apply the [offset:] tag to all timestamps and drop the tag
+  pub fn bake_offset(&mut self) {
+    let offset = self.offset();
+    self.shift(offset.saturating_neg());
+    self.remove_tags(&["offset"]);
+  }
+
+  pub fn has_timestamps(&self) -> bool {
+    self.lines.iter().any(|line| !line.timestamps.is_empty())
+  }
+
+  // All timestamped lines in playback order. Lines with several timestamps
+  // (e.g. a repeated chorus) appear once per timestamp.
+  pub fn timed_lines(&self) -> Vec<TimedLine<'_>> {
+    let offset = self.offset();
+    let mut timed_lines: Vec<TimedLine> = self.lines
+      .iter()
+      .flat_map(|line| {
+        line.timestamps.iter().map(move |timestamp| TimedLine {
+          start_ms: timestamp.saturating_sub(offset).max(0),
+          line,
+        })
+      })
+      .collect();
+    timed_lines.sort_by_key(|timed_line| timed_line.start_ms);
+    timed_lines
+  }
+
+  pub fn to_plain(&self) -> String {
+    // Untimed lyrics have no playback order, keep them as written
+    let texts: Vec<&str> = if self.has_timestamps() {
+      self.timed_lines().iter().map(|timed_line| timed_line.line.text.as_str()).collect()
+    } else {
+      self.lines.iter().map(|line| line.text.as_str()).collect()
+    };
+    texts.join("\n").trim().to_owned()
+  }
+}
+
+impl fmt::Display for Lrc {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // Only write milliseconds when centiseconds would lose precision
+    let precise = self.millisecond_precision || self.lines.iter().any(|line| {
+      line.timestamps.iter().any(|timestamp| timestamp % 10 != 0)
+        || line.words.iter().any(|word| word.start_ms % 10 != 0)
+    });
+    let format = |timestamp: i64| if precise { format_precise_timestamp(timestamp) } else { format_timestamp(timestamp) };
+
+    let mut output = vec![];
+
+    for tag in &self.tags {
+      output.push(format!("[{}:{}]", tag.key, tag.value));
+    }
+
+    let mut lines = vec![];
+    for line in &self.lines {
+      let timestamps: String = line.timestamps
+        .iter()
+        .map(|timestamp| format!("[{}]", format(*timestamp)))
+        .collect();
+      let voice = match line.voice {
+        Some(ref voice) => format!("{}: ", voice),
+        None => "".to_owned(),
+      };
+      let text = if line.words.is_empty() {
+        line.text.to_owned()
+      } else {
+        line.words
+          .iter()
+          .map(|word| format!("<{}>{}", format(word.start_ms), word.text))
+          .collect()
+      };
+      lines.push((line.line_number, format!("{}{}{}", timestamps, voice, text)));
+    }
+
+    // Lines that could not be parsed are kept as they were, in their original place
+    for error in &self.errors {
+      lines.push((error.line_number, error.raw_line.trim().to_owned()));
+    }
+    lines.sort_by_key(|(line_number, _)| *line_number);
+    output.extend(lines.into_iter().map(|(_, line)| line));
+
+    write!(f, "{}", output.join("\n"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn timestamps(lrc: &Lrc) -> Vec<Vec<i64>> {
+    lrc.lines.iter().map(|line| line.timestamps.clone()).collect()
+  }
+
+  #[test]
+  fn timestamps_are_parsed_in_every_precision() {
+    assert_eq!(parse_timestamp("01:02"), Some(62_000));
+    assert_eq!(parse_timestamp("01:02.5"), Some(62_500));
+    assert_eq!(parse_timestamp("01:02.34"), Some(62_340));
+    assert_eq!(parse_timestamp("01:02:345"), Some(62_345));
+    assert_eq!(parse_timestamp("100:00.00"), Some(6_000_000));
+    assert_eq!(parse_timestamp("01:60.00"), None);
+    assert_eq!(parse_timestamp("ar:Artist"), None);
+  }
+
+  #[test]
+  fn tags_and_lines_are_separated() {
+    let lrc = parse("[ti:Song]\n[ar: Artist ]\n\n[00:01.00]Hello\n[00:02.00] World ");
+    assert_eq!(lrc.tag("TI"), Some("Song"));
+    assert_eq!(lrc.tag("ar"), Some("Artist"));
+    assert_eq!(lrc.lines.len(), 2);
+    assert_eq!(lrc.lines[1].line_number, 5);
+    assert_eq!(lrc.lines[1].text, "World");
+    assert!(lrc.errors.is_empty());
+  }
+
+  #[test]
+  fn line_can_have_several_timestamps() {
+    let lrc = parse("[00:01.00][00:10.00]Chorus\n[00:05.00]Verse");
+    assert_eq!(timestamps(&lrc), [vec![1000, 10_000], vec![5000]]);
+    assert_eq!(
+      lrc.timed_lines().iter().map(|timed_line| (timed_line.start_ms, timed_line.line.text.as_str())).collect::<Vec<_>>(),
+      [(1000, "Chorus"), (5000, "Verse"), (10_000, "Chorus")],
+    );
+    assert_eq!(lrc.to_plain(), "Chorus\nVerse\nChorus");
+    assert_eq!(lrc.to_string(), "[00:01.00][00:10.00]Chorus\n[00:05.00]Verse");
+  }
+
+  #[test]
+  fn overflowing_timestamp_is_malformed() {
+    assert_eq!(parse_timestamp("999999999999999:00.00"), None);
+    assert_eq!(parse_timestamp("99999999999999999999:00.00"), None);
+
+    let lrc = parse("[999999999999999:00.00]Hello");
+    assert!(lrc.lines.is_empty());
+    assert_eq!(lrc.errors[0].kind, ParseErrorKind::MalformedTimestamp("999999999999999:00.00".to_owned()));
+  }
+
+  #[test]
+  fn shift_saturates() {
+    let mut lrc = parse("[00:01.00]<00:01.00>Hello");
+    lrc.shift(i64::MAX);
+    assert_eq!(lrc.lines[0].timestamps, [i64::MAX]);
+    assert_eq!(lrc.lines[0].words[0].start_ms, i64::MAX);
+    lrc.shift(i64::MIN);
+    assert_eq!(lrc.lines[0].timestamps, [0]);
+
+    // Offset tags are user input as well
+    assert_eq!(apply_offset("[offset:9223372036854775807]\n[00:01.00]Hello", 0), "[00:00.00]Hello");
+    assert_eq!(apply_offset("[offset:-9223372036854775808]\n[00:01.00]Hello", 0), "[153722867280912:55.807]Hello");
+  }
+
+  #[test]
+  fn brackets_in_text_are_kept() {
+    let lrc = parse("[00:01.00][Chorus] Hello\n[Verse 2]");
+    assert_eq!(lrc.lines[0].text, "[Chorus] Hello");
+    assert!(lrc.lines[1].timestamps.is_empty());
+    assert_eq!(lrc.lines[1].text, "[Verse 2]");
+    assert!(lrc.tags.is_empty());
+  }
+
+  #[test]
+  fn malformed_timestamp_is_reported() {
+    let lrc = parse("[00:01.00]One\n[00:61.00]Two");
+    assert_eq!(lrc.lines.len(), 1);
+    assert_eq!(lrc.errors, [ParseError {
+      line_number: 2,
+      kind: ParseErrorKind::MalformedTimestamp("00:61.00".to_owned()),
+      raw_line: "[00:61.00]Two".to_owned(),
+    }]);
+  }
+
+  #[test]
+  fn timestamps_are_written_in_centiseconds_by_default() {
+    assert_eq!(format_timestamp(62_345), "01:02.35");
+    assert_eq!(format_timestamp(-5), "00:00.00");
+    assert_eq!(parse("[1:2.5]Hello").to_string(), "[01:02.50]Hello");
+  }
+
+  #[test]
+  fn word_timings_and_voice_are_parsed() {
+    let lrc = parse("[00:01.00]v1: <00:01.00>Hello <00:01.50>world<00:02.00>");
+    let line = &lrc.lines[0];
+    assert_eq!(line.voice.as_deref(), Some("v1"));
+    assert_eq!(line.text, "Hello world");
+    assert_eq!(
+      line.words.iter().map(|word| (word.start_ms, word.text.as_str())).collect::<Vec<_>>(),
+      [(1000, "Hello "), (1500, "world"), (2000, "")],
+    );
+    assert!(lrc.is_enhanced());
+  }
+
+  #[test]
+  fn text_before_the_first_word_timing_starts_with_the_line() {
+    let lrc = parse("[00:01.00]Hello <00:01.50>world");
+    assert_eq!(
+      lrc.lines[0].words.iter().map(|word| (word.start_ms, word.text.as_str())).collect::<Vec<_>>(),
+      [(1000, "Hello "), (1500, "world")],
+    );
+  }
+
+  #[test]
+  fn enhanced_lyrics_round_trip() {
+    let input = "[00:01.00]v1: <00:01.00>Hello <00:01.50>world\n[00:03.00]v2: <00:03.00>Bye";
+    let lrc = parse(input);
+    assert_eq!(lrc.to_string(), input);
+    assert_eq!(lrc.to_line_level().to_string(), "[00:01.00]Hello world\n[00:03.00]Bye");
+    assert!(!parse("[00:01.00]Hello").is_enhanced());
+  }
+
+  #[test]
+  fn shift_and_scale_move_words_too() {
+    let mut lrc = parse("[00:01.00]<00:01.00>Hello <00:02.00>world");
+    lrc.scale(2.0);
+    lrc.shift(-2500);
+    assert_eq!(lrc.to_string(), "[00:00.00]<00:00.00>Hello <00:01.50>world");
+  }
+
+  #[test]
+  fn offset_is_baked_into_every_timestamp() {
+    let mut lrc = parse("[offset:500]\n[ti:Song]\n[00:01.00][00:10.00]Chorus\n[00:12.00]<00:12.00>Verse <00:12.50>line");
+    assert_eq!(lrc.offset(), 500);
+    assert_eq!(lrc.timed_lines().iter().map(|timed_line| timed_line.start_ms).collect::<Vec<_>>(), [500, 9500, 11_500]);
+
+    lrc.bake_offset();
+    assert_eq!(lrc.offset(), 0);
+    assert_eq!(lrc.to_string(), "[ti:Song]\n[00:00.50][00:09.50]Chorus\n[00:11.50]<00:11.50>Verse <00:12.00>line");
+  }
+
+  #[test]
+  fn negative_offset_delays_lines() {
+    assert_eq!(apply_offset("[offset:-250]\n[00:01.00]Hello", 0), "[00:01.25]Hello");
+    assert_eq!(apply_offset("[00:01.00]Hello", -250), "[00:01.25]Hello");
+    assert_eq!(apply_offset("[00:01.00]Hello", 5000), "[00:00.00]Hello");
+  }
+
+  #[test]
+  fn offset_keeps_millisecond_precision() {
+    assert_eq!(apply_offset("[00:01.234]Hello", 0), "[00:01.234]Hello");
+    assert_eq!(apply_offset("[00:01.234]Hello\n[00:02.50]World", 1000), "[00:00.234]Hello\n[00:01.500]World");
+    assert_eq!(apply_offset("[00:01.23]Hello", 1), "[00:01.229]Hello");
+  }
+
+  #[test]
+  fn offset_keeps_centisecond_format() {
+    assert_eq!(apply_offset("[00:01.23]Hello\n[00:02.50]World", 20), "[00:01.21]Hello\n[00:02.48]World");
+  }
+
+  #[test]
+  fn offset_keeps_unparsable_lines() {
+    let input = "[00:01.00]One\n[0x:02.00]Two\n[00:03.00]Three";
+    assert_eq!(apply_offset(input, 0), input);
+  }
+}
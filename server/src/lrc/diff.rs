@@ -0,0 +1,226 @@
+use serde::Serialize;
+use super::{parse, Lrc};
+
+// Largest LCS table built for the changed middle of two revisions, e.g. 500 × 500 lines
+const MAX_LCS_CELLS: usize = 250_000;
+
+// A lyrics line as compared by the diff. start_ms is None when comparing plain lyrics.
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+  pub start_ms: Option<i64>,
+  pub text: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+  Unchanged,
+  Added,
+  Removed,
+  Retimed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LineChange {
+  pub kind: ChangeKind,
+  pub text: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub from_start_ms: Option<i64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub to_start_ms: Option<i64>,
+}
+
+pub fn timed_diff_lines(lrc: &Lrc) -> Vec<DiffLine> {
+  lrc.timed_lines()
+    .iter()
+    .filter(|timed_line| !timed_line.line.text.is_empty())
+    .map(|timed_line| DiffLine { start_ms: Some(timed_line.start_ms), text: timed_line.line.text.to_owned() })
+    .collect()
+}
+
+pub fn plain_diff_lines(input: &str) -> Vec<DiffLine> {
+  input
+    .lines()
+    .map(|line| line.trim())
+    .filter(|line| !line.is_empty())
+    .map(|line| DiffLine { start_ms: None, text: line.to_owned() })
+    .collect()
+}
+
+// Lines to compare for a revision: timed lines when both sides are synced, otherwise
+// plain text lines (falling back to the text of the synced lyrics)
+pub fn revision_diff_lines(plain_lyrics: Option<&str>, synced_lyrics: Option<&str>, timed: bool) -> Vec<DiffLine> {
+  match (synced_lyrics, timed) {
+    (Some(synced_lyrics), true) => timed_diff_lines(&parse(synced_lyrics)),
+    (Some(synced_lyrics), false) if plain_lyrics.is_none() => plain_diff_lines(&parse(synced_lyrics).to_plain()),
+    _ => plain_diff_lines(plain_lyrics.unwrap_or_default()),
+  }
+}
+
+// Match lines by text with a longest common subsequence, then report matched lines
+// whose timestamp moved as retimed
+pub fn diff(from: &[DiffLine], to: &[DiffLine]) -> Vec<LineChange> {
+  // Lines shared at the start and the end are matched without the LCS table
+  let prefix = from.iter().zip(to).take_while(|(from_line, to_line)| from_line.text == to_line.text).count();
+  let suffix = from[prefix..].iter().rev()
+    .zip(to[prefix..].iter().rev())
+    .take_while(|(from_line, to_line)| from_line.text == to_line.text)
+    .count();
+  let from_middle = &from[prefix..from.len() - suffix];
+  let to_middle = &to[prefix..to.len() - suffix];
+
+  let mut changes: Vec<LineChange> = from[..prefix]
+    .iter()
+    .zip(&to[..prefix])
+    .map(|(from_line, to_line)| matched(from_line, to_line))
+    .collect();
+
+  // The table grows with both sides, so unrelated lyrics beyond the limit are reported as
+  // replaced as a whole rather than aligned line by line
+  if from_middle.len() * to_middle.len() > MAX_LCS_CELLS {
+    changes.extend(from_middle.iter().map(removed));
+    changes.extend(to_middle.iter().map(added));
+  } else {
+    changes.extend(lcs_diff(from_middle, to_middle));
+  }
+
+  changes.extend(
+    from[from.len() - suffix..]
+      .iter()
+      .zip(&to[to.len() - suffix..])
+      .map(|(from_line, to_line)| matched(from_line, to_line)),
+  );
+  changes
+}
+
+fn lcs_diff(from: &[DiffLine], to: &[DiffLine]) -> Vec<LineChange> {
+  // lengths[i][j] is the LCS length of from[i..] and to[j..]
+  let mut lengths = vec![vec![0_usize; to.len() + 1]; from.len() + 1];
+  for i in (0..from.len()).rev() {
+    for j in (0..to.len()).rev() {
+      lengths[i][j] = if from[i].text == to[j].text {
+        lengths[i + 1][j + 1] + 1
+      } else {
+        lengths[i + 1][j].max(lengths[i][j + 1])
+      };
+    }
+  }
+
+  let mut changes = vec![];
+  let (mut i, mut j) = (0, 0);
+
+  while i < from.len() || j < to.len() {
+    if i < from.len() && j < to.len() && from[i].text == to[j].text {
+      changes.push(matched(&from[i], &to[j]));
+      i += 1;
+      j += 1;
+    } else if i < from.len() && (j == to.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+      changes.push(removed(&from[i]));
+      i += 1;
+    } else {
+      changes.push(added(&to[j]));
+      j += 1;
+    }
+  }
+
+  changes
+}
+
+fn matched(from_line: &DiffLine, to_line: &DiffLine) -> LineChange {
+  let kind = if from_line.start_ms == to_line.start_ms { ChangeKind::Unchanged } else { ChangeKind::Retimed };
+  LineChange {
+    kind,
+    text: to_line.text.to_owned(),
+    from_start_ms: from_line.start_ms,
+    to_start_ms: to_line.start_ms,
+  }
+}
+
+fn removed(line: &DiffLine) -> LineChange {
+  LineChange {
+    kind: ChangeKind::Removed,
+    text: line.text.to_owned(),
+    from_start_ms: line.start_ms,
+    to_start_ms: None,
+  }
+}
+
+fn added(line: &DiffLine) -> LineChange {
+  LineChange {
+    kind: ChangeKind::Added,
+    text: line.text.to_owned(),
+    from_start_ms: None,
+    to_start_ms: line.start_ms,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn summary(changes: &[LineChange]) -> Vec<(ChangeKind, &str)> {
+    changes.iter().map(|change| (change.kind, change.text.as_str())).collect()
+  }
+
+  #[test]
+  fn moved_timestamps_are_retimed() {
+    let from = timed_diff_lines(&parse("[00:01.00]a\n[00:02.00]b\n[00:03.00]c"));
+    let to = timed_diff_lines(&parse("[00:01.00]a\n[00:02.50]b\n[00:03.00]c"));
+    let changes = diff(&from, &to);
+    assert_eq!(summary(&changes), [(ChangeKind::Unchanged, "a"), (ChangeKind::Retimed, "b"), (ChangeKind::Unchanged, "c")]);
+    assert_eq!((changes[1].from_start_ms, changes[1].to_start_ms), (Some(2000), Some(2500)));
+  }
+
+  #[test]
+  fn offset_tag_counts_as_retiming() {
+    let from = timed_diff_lines(&parse("[00:01.00]a"));
+    let to = timed_diff_lines(&parse("[offset:500]\n[00:01.00]a"));
+    assert_eq!(summary(&diff(&from, &to)), [(ChangeKind::Retimed, "a")]);
+  }
+
+  #[test]
+  fn changed_lines_are_removed_and_added() {
+    let from = plain_diff_lines("a\nb\nc\nd");
+    let to = plain_diff_lines("a\nc\nB\nd\ne");
+    assert_eq!(summary(&diff(&from, &to)), [
+      (ChangeKind::Unchanged, "a"),
+      (ChangeKind::Removed, "b"),
+      (ChangeKind::Unchanged, "c"),
+      (ChangeKind::Added, "B"),
+      (ChangeKind::Unchanged, "d"),
+      (ChangeKind::Added, "e"),
+    ]);
+  }
+
+  #[test]
+  fn repeated_lines_are_compared_per_timestamp() {
+    let from = timed_diff_lines(&parse("[00:01.00][00:05.00]chorus\n[00:03.00]verse"));
+    let to = timed_diff_lines(&parse("[00:01.00][00:06.00]chorus\n[00:03.00]verse"));
+    assert_eq!(summary(&diff(&from, &to)), [
+      (ChangeKind::Unchanged, "chorus"),
+      (ChangeKind::Unchanged, "verse"),
+      (ChangeKind::Retimed, "chorus"),
+    ]);
+  }
+
+  #[test]
+  fn synced_lyrics_stand_in_for_missing_plain_lyrics() {
+    let lines = revision_diff_lines(None, Some("[00:01.00]a\n[00:02.00]b"), false);
+    assert_eq!(lines.iter().map(|line| (line.start_ms, line.text.as_str())).collect::<Vec<_>>(), [(None, "a"), (None, "b")]);
+  }
+
+  #[test]
+  fn large_changes_are_not_aligned_line_by_line() {
+    let from_text: Vec<String> = (0..1000).map(|index| format!("from {}", index)).collect();
+    let to_text: Vec<String> = (0..1000).map(|index| format!("to {}", index)).collect();
+    let from = plain_diff_lines(&format!("first\n{}\nlast", from_text.join("\n")));
+    let to = plain_diff_lines(&format!("first\n{}\nlast", to_text.join("\n")));
+
+    let changes = diff(&from, &to);
+    assert_eq!(changes.len(), 2002);
+    assert_eq!(summary(&changes[..2]), [(ChangeKind::Unchanged, "first"), (ChangeKind::Removed, "from 0")]);
+    assert_eq!(summary(&changes[1001..1002]), [(ChangeKind::Added, "to 0")]);
+    assert_eq!(summary(&changes[2001..]), [(ChangeKind::Unchanged, "last")]);
+  }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+use serde::Serialize;
+use super::{format_timestamp, parse_timestamp, Lrc, ParseErrorKind};
+use crate::utils::prepare_input;
+
+// ID tags that repeat the track metadata
+pub const METADATA_TAGS: [&str; 4] = ["ti", "ar", "al", "length"];
+
+// Same tolerance as track lookups by metadata
+const MAX_LENGTH_DIFFERENCE: f64 = 2.0;
+
+pub struct TrackMetadata<'a> {
+  pub track_name: &'a str,
+  pub artist_name: &'a str,
+  pub album_name: &'a str,
+  pub duration: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LintIssue {
+  pub line_number: Option<usize>,
+  pub code: &'static str,
+  pub reason: String,
+}
+
+impl fmt::Display for LintIssue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.line_number {
+      Some(line_number) => write!(f, "line {}: {}", line_number, self.reason),
+      None => write!(f, "{}", self.reason),
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct LintReport {
+  pub errors: Vec<LintIssue>,
+  pub warnings: Vec<LintIssue>,
+}
+
+impl LintReport {
+  fn error(&mut self, line_number: Option<usize>, code: &'static str, reason: String) {
+    self.errors.push(LintIssue { line_number, code, reason });
+  }
+
+  fn warning(&mut self, line_number: Option<usize>, code: &'static str, reason: String) {
+    self.warnings.push(LintIssue { line_number, code, reason });
+  }
+}
+
+pub fn lint(lrc: &Lrc, metadata: &TrackMetadata) -> LintReport {
+  let mut report = LintReport::default();
+  check(lrc, Some(metadata.duration), &mut report);
+  check_metadata_tags(lrc, metadata, &mut report);
+  report.errors.sort_by_key(|issue| issue.line_number);
+  report.warnings.sort_by_key(|issue| issue.line_number);
+  report
+}
+
+// Lines the parser could not make sense of, as issues
+pub fn parse_errors(lrc: &Lrc) -> Vec<LintIssue> {
+  lrc.errors
+    .iter()
+    .map(|error| match &error.kind {
+      ParseErrorKind::MalformedTimestamp(timestamp) => LintIssue {
+        line_number: Some(error.line_number),
+        code: "malformed_timestamp",
+        reason: format!("malformed timestamp [{}]", timestamp),
+      },
+    })
+    .collect()
+}
+
+fn check(lrc: &Lrc, duration: Option<f64>, report: &mut LintReport) {
+  report.errors.extend(parse_errors(lrc));
+
+  if lrc.lines.is_empty() {
+    if !lrc.is_instrumental() && lrc.errors.is_empty() {
+      report.error(None, "empty_lyrics", "synced lyrics do not contain any line".to_owned());
+    }
+    return;
+  }
+
+  if lrc.is_instrumental() {
+    report.warning(
+      None,
+      "instrumental_with_lyrics",
+      "lyrics are marked as instrumental, their lines will be discarded".to_owned(),
+    );
+  }
+
+  if !lrc.has_timestamps() {
+    report.error(
+      None,
+      "missing_timestamps",
+      "synced lyrics do not contain any timestamp, send them as plain lyrics instead".to_owned(),
+    );
+    return;
+  }
+
+  let mut previous_timestamp: Option<i64> = None;
+
+  for line in &lrc.lines {
+    let Some(timestamp) = line.timestamps.iter().min().copied() else {
+      // Players skip these, e.g. a section header left in by hand
+      report.warning(
+        Some(line.line_number),
+        "missing_timestamp",
+        "line does not have a timestamp and will be skipped".to_owned(),
+      );
+      continue;
+    };
+
+    if let Some(previous_timestamp) = previous_timestamp {
+      if timestamp < previous_timestamp {
+        report.error(
+          Some(line.line_number),
+          "timestamp_backwards",
+          format!(
+            "timestamp {} is earlier than the previous line's {}",
+            format_timestamp(timestamp),
+            format_timestamp(previous_timestamp),
+          ),
+        );
+      }
+    }
+
+    previous_timestamp = Some(timestamp);
+
+    for pair in line.words.windows(2) {
+      if pair[1].start_ms < pair[0].start_ms {
+        report.error(
+          Some(line.line_number),
+          "word_timestamp_backwards",
+          format!(
+            "word timestamp {} is earlier than the previous word's {}",
+            format_timestamp(pair[1].start_ms),
+            format_timestamp(pair[0].start_ms),
+          ),
+        );
+      }
+    }
+  }
+
+  let timed_lines = lrc.timed_lines();
+
+  for pair in timed_lines.windows(2) {
+    if pair[0].start_ms == pair[1].start_ms {
+      report.warning(
+        Some(pair[1].line.line_number),
+        "duplicate_timestamp",
+        format!("timestamp {} is used by more than one line", format_timestamp(pair[1].start_ms)),
+      );
+    }
+  }
+
+  if let (Some(duration), Some(last_line)) = (duration, timed_lines.last()) {
+    if last_line.start_ms as f64 > duration * 1000.0 {
+      report.error(
+        Some(last_line.line.line_number),
+        "timestamp_exceeds_duration",
+        format!(
+          "timestamp {} is past the track duration of {} seconds",
+          format_timestamp(last_line.start_ms),
+          duration,
+        ),
+      );
+    }
+  }
+}
+
+// Compare [ti:], [ar:], [al:] and [length:] against the submitted metadata, to catch
+// lyrics pasted for the wrong song
+fn check_metadata_tags(lrc: &Lrc, metadata: &TrackMetadata, report: &mut LintReport) {
+  for tag in &lrc.tags {
+    let expected = match tag.key.to_lowercase().as_str() {
+      "ti" => metadata.track_name,
+      "ar" => metadata.artist_name,
+      "al" => metadata.album_name,
+      "length" => {
+        let Some(length) = parse_length(&tag.value) else {
+          continue;
+        };
+        if (length - metadata.duration).abs() > MAX_LENGTH_DIFFERENCE {
+          report.error(
+            Some(tag.line_number),
+            "tag_mismatch",
+            format!("[length:{}] does not match the track duration of {} seconds", tag.value, metadata.duration),
+          );
+        }
+        continue;
+      },
+      _ => continue,
+    };
+
+    let value = prepare_input(&tag.value);
+    let expected_value = prepare_input(expected);
+
+    // Allow one to contain the other, e.g. "Artist" and "Artist feat. Someone"
+    if !value.is_empty() && !expected_value.is_empty()
+      && !value.contains(&expected_value) && !expected_value.contains(&value) {
+      report.error(
+        Some(tag.line_number),
+        "tag_mismatch",
+        format!("[{}:{}] does not match \"{}\"", tag.key, tag.value, expected),
+      );
+    }
+  }
+}
+
+// [length:] is either mm:ss(.xx) or a number of seconds
+pub fn parse_length(value: &str) -> Option<f64> {
+  parse_timestamp(value)
+    .map(|ms| ms as f64 / 1000.0)
+    .or_else(|| value.trim().parse::<f64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lrc::parse;
+
+  const METADATA: TrackMetadata = TrackMetadata {
+    track_name: "Song",
+    artist_name: "Artist",
+    album_name: "Album",
+    duration: 180.0,
+  };
+
+  fn codes(issues: &[LintIssue]) -> Vec<(Option<usize>, &'static str)> {
+    issues.iter().map(|issue| (issue.line_number, issue.code)).collect()
+  }
+
+  #[test]
+  fn clean_lyrics_have_no_issues() {
+    let report = lint(&parse("[ti:Song]\n[ar:Artist feat. Someone]\n[00:01.00]a\n[00:02.00]b"), &METADATA);
+    assert!(report.errors.is_empty());
+    assert!(report.warnings.is_empty());
+  }
+
+  #[test]
+  fn malformed_timestamp_is_an_error() {
+    let report = lint(&parse("[00:01.00]a\n[00:x2.00]b"), &METADATA);
+    assert_eq!(codes(&report.errors), [(Some(2), "malformed_timestamp")]);
+  }
+
+  #[test]
+  fn empty_lyrics_are_an_error() {
+    let report = lint(&parse("[ti:Song]\n"), &METADATA);
+    assert_eq!(codes(&report.errors), [(None, "empty_lyrics")]);
+  }
+
+  #[test]
+  fn instrumental_with_lyrics_is_a_warning() {
+    let report = lint(&parse("[au:instrumental]\n[00:01.00]a"), &METADATA);
+    assert!(report.errors.is_empty());
+    assert_eq!(codes(&report.warnings), [(None, "instrumental_with_lyrics")]);
+  }
+
+  #[test]
+  fn wholly_untimed_lyrics_are_an_error() {
+    let report = lint(&parse("a\nb"), &METADATA);
+    assert_eq!(codes(&report.errors), [(None, "missing_timestamps")]);
+  }
+
+  #[test]
+  fn untimed_line_in_synced_lyrics_is_a_warning() {
+    let report = lint(&parse("[00:01.00]a\nChorus\n[00:02.00]b"), &METADATA);
+    assert!(report.errors.is_empty());
+    assert_eq!(codes(&report.warnings), [(Some(2), "missing_timestamp")]);
+  }
+
+  #[test]
+  fn backwards_timestamps_are_errors() {
+    let report = lint(&parse("[00:02.00]a\n[00:01.00]b\n[00:03.00]<00:03.50>c <00:03.20>d"), &METADATA);
+    assert_eq!(codes(&report.errors), [(Some(2), "timestamp_backwards"), (Some(3), "word_timestamp_backwards")]);
+  }
+
+  #[test]
+  fn duplicate_timestamp_is_a_warning() {
+    let report = lint(&parse("[00:01.00]a\n[00:01.00]b"), &METADATA);
+    assert!(report.errors.is_empty());
+    assert_eq!(codes(&report.warnings), [(Some(2), "duplicate_timestamp")]);
+  }
+
+  #[test]
+  fn repeated_line_is_checked_at_each_timestamp() {
+    let report = lint(&parse("[00:01.00][00:05.00]chorus\n[00:03.00]verse"), &METADATA);
+    assert!(report.errors.is_empty());
+    assert!(report.warnings.is_empty());
+  }
+
+  #[test]
+  fn timestamp_past_duration_is_an_error() {
+    let report = lint(&parse("[00:01.00]a\n[03:10.00]b"), &METADATA);
+    assert_eq!(codes(&report.errors), [(Some(2), "timestamp_exceeds_duration")]);
+  }
+
+  #[test]
+  fn offset_is_applied_before_checking_duration() {
+    let report = lint(&parse("[offset:+20000]\n[03:10.00]a"), &METADATA);
+    assert!(report.errors.is_empty());
+  }
+
+  #[test]
+  fn mismatching_tags_are_errors() {
+    let report = lint(&parse("[ti:Different]\n[ar:Artist]\n[al:Other]\n[length:2:30]\n[00:01.00]a"), &METADATA);
+    assert_eq!(codes(&report.errors), [(Some(1), "tag_mismatch"), (Some(3), "tag_mismatch"), (Some(4), "tag_mismatch")]);
+  }
+
+  #[test]
+  fn length_within_tolerance_matches() {
+    assert_eq!(parse_length("3:01"), Some(181.0));
+    assert_eq!(parse_length("179.5"), Some(179.5));
+    let report = lint(&parse("[length:3:01]\n[00:01.00]a"), &METADATA);
+    assert!(report.errors.is_empty());
+  }
+}
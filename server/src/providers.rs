@@ -1 +1,73 @@
+use std::{path::PathBuf, time::Duration};
+use anyhow::{bail, Context, Result};
+use axum::async_trait;
+use crate::queue::ScrapedData;
+use command::CommandProvider;
+use local::LocalProvider;
+use noop::NoopProvider;
+use upstream::UpstreamProvider;
+
 pub mod noop;
+pub mod local;
+pub mod upstream;
+pub mod command;
+
+// A source the queue can look up lyrics of missing tracks from
+#[async_trait]
+pub trait LyricsProvider: Send + Sync {
+  // Stored as the source of the lyrics found by this provider
+  fn name(&self) -> &str;
+
+  async fn retrieve_lyrics(&self, track_name: &str, artist_name: &str, album_name: &str, duration: f64) -> Result<Option<ScrapedData>>;
+}
+
+pub struct ProvidersConfig {
+  // Provider names, in the order they are tried
+  pub names: Vec<String>,
+  // Directory the local provider reads lyrics files from
+  pub lrc_directory: Option<PathBuf>,
+  // Base URL of the LRCLIB-compatible server the upstream provider queries
+  pub upstream_url: Option<String>,
+  // Stored as the source of lyrics found upstream, defaults to the host of the upstream URL
+  pub upstream_name: Option<String>,
+  pub upstream_timeout: Duration,
+  pub upstream_max_concurrent_requests: usize,
+  // Executable the command provider runs for each missing track
+  pub command: Option<PathBuf>,
+  pub command_timeout: Duration,
+}
+
+pub fn build_providers(config: &ProvidersConfig) -> Result<Vec<Box<dyn LyricsProvider>>> {
+  let mut providers: Vec<Box<dyn LyricsProvider>> = vec![];
+
+  for name in &config.names {
+    match name.trim() {
+      "noop" => providers.push(Box::new(NoopProvider::new())),
+      "local" => {
+        let directory = config.lrc_directory.as_ref().context("the local provider requires a lyrics directory")?;
+        providers.push(Box::new(LocalProvider::new(directory)?));
+      },
+      "upstream" => {
+        let url = config.upstream_url.as_deref().context("the upstream provider requires an upstream url")?;
+        let name = match config.upstream_name {
+          Some(ref name) => name.to_owned(),
+          None => reqwest::Url::parse(url)?.host_str().context("the upstream url has no host")?.to_owned(),
+        };
+        providers.push(Box::new(UpstreamProvider::new(
+          &name,
+          url,
+          config.upstream_timeout,
+          config.upstream_max_concurrent_requests,
+        )?));
+      },
+      "command" => {
+        let program = config.command.as_ref().context("the command provider requires a command")?;
+        providers.push(Box::new(CommandProvider::new(program, config.command_timeout)?));
+      },
+      "" => {},
+      name => bail!("unknown lyrics provider: {}", name),
+    }
+  }
+
+  Ok(providers)
+}
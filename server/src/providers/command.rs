@@ -0,0 +1,170 @@
+use std::{path::{Path, PathBuf}, process::Stdio, time::Duration};
+use anyhow::{bail, Context, Result};
+use axum::async_trait;
+use serde::Serialize;
+use tokio::{io::AsyncWriteExt, process::Command};
+use crate::{providers::LyricsProvider, queue::ScrapedData};
+
+// Exit code (EX_TEMPFAIL) a command uses to ask for the track to be retried later
+const EXIT_TEMPORARY_FAILURE: i32 = 75;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandInput<'a> {
+  track_name: &'a str,
+  artist_name: &'a str,
+  album_name: &'a str,
+  duration: f64,
+}
+
+// Runs an executable for each missing track. The track is written to its stdin as JSON, e.g.
+// {"trackName":"…","artistName":"…","albumName":"…","duration":233.0}, and the command answers on stdout
+// with {"plainLyrics":"…","syncedLyrics":"…","instrumental":false}, or nothing when it found nothing.
+//
+// Exit codes: 0 means stdout holds the answer and 75 means a temporary failure, the track is then retried
+// later like for any other provider error. Anything else means the command cannot handle the track, which
+// is logged and treated as not found so the track is not retried.
+pub struct CommandProvider {
+  name: String,
+  program: PathBuf,
+  timeout: Duration,
+}
+
+impl CommandProvider {
+  pub fn new(program: &Path, timeout: Duration) -> Result<Self> {
+    if !program.is_file() {
+      bail!("provider command {} does not exist", program.display());
+    }
+
+    // Lyrics found by the command are attributed to its file name
+    let name = program.file_stem()
+      .and_then(|name| name.to_str())
+      .context("the provider command has no file name")?
+      .to_owned();
+
+    Ok(Self { name, program: program.to_path_buf(), timeout })
+  }
+}
+
+#[async_trait]
+impl LyricsProvider for CommandProvider {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  async fn retrieve_lyrics(&self, track_name: &str, artist_name: &str, album_name: &str, duration: f64) -> Result<Option<ScrapedData>> {
+    let input = serde_json::to_vec(&CommandInput { track_name, artist_name, album_name, duration })?;
+
+    let mut child = Command::new(&self.program)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .kill_on_drop(true)
+      .spawn()
+      .with_context(|| format!("failed to start {}", self.program.display()))?;
+
+    let mut stdin = child.stdin.take().context("failed to open stdin of the provider command")?;
+    stdin.write_all(&input).await?;
+    // Close stdin so the command sees the end of its input
+    drop(stdin);
+
+    // The child is killed when dropped, which also happens on timeout
+    let output = tokio::time::timeout(self.timeout, child.wait_with_output()).await
+      .with_context(|| format!("{} timed out after {} seconds", self.name, self.timeout.as_secs()))??;
+
+    match output.status.code() {
+      Some(0) => {},
+      Some(EXIT_TEMPORARY_FAILURE) => bail!("{} failed temporarily: {}", self.name, String::from_utf8_lossy(&output.stderr).trim()),
+      _ => {
+        tracing::error!(
+          message = "provider command failed",
+          provider = self.name,
+          track_name = track_name,
+          artist_name = artist_name,
+          album_name = album_name,
+          duration = duration,
+          status = output.status.to_string(),
+          error = String::from_utf8_lossy(&output.stderr).trim(),
+          queue = true,
+        );
+        return Ok(None);
+      },
+    }
+
+    let stdout = String::from_utf8(output.stdout).context("the provider command wrote invalid UTF-8")?;
+    if stdout.trim().is_empty() {
+      return Ok(None);
+    }
+
+    let data = serde_json::from_str::<ScrapedData>(&stdout)
+      .with_context(|| format!("{} wrote invalid lyrics JSON", self.name))?;
+    if !data.instrumental && data.plain_lyrics.is_none() && data.synced_lyrics.is_none() {
+      return Ok(None);
+    }
+
+    Ok(Some(data))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn provider(timeout: Duration) -> CommandProvider {
+    let program = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/provider-command.sh");
+    CommandProvider::new(&program, timeout).unwrap()
+  }
+
+  #[tokio::test]
+  async fn answer_is_read_from_stdout() {
+    let provider = provider(Duration::from_secs(5));
+    assert_eq!(provider.name(), "provider-command");
+    let data = provider.retrieve_lyrics("hit", "Artist", "Album", 180.0).await.unwrap().unwrap();
+    assert_eq!(data.plain_lyrics.as_deref(), Some("a"));
+    assert_eq!(data.synced_lyrics.as_deref(), Some("[00:01.00]a"));
+  }
+
+  #[tokio::test]
+  async fn empty_stdout_is_not_found() {
+    let provider = provider(Duration::from_secs(5));
+    assert!(provider.retrieve_lyrics("missing", "Artist", "Album", 180.0).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn temporary_failure_is_an_error_to_retry() {
+    let provider = provider(Duration::from_secs(5));
+    let err = provider.retrieve_lyrics("busy", "Artist", "Album", 180.0).await.unwrap_err();
+    assert!(err.to_string().contains("rate limited"));
+  }
+
+  #[tokio::test]
+  async fn other_exit_code_is_not_found() {
+    let provider = provider(Duration::from_secs(5));
+    assert!(provider.retrieve_lyrics("broken", "Artist", "Album", 180.0).await.unwrap().is_none());
+  }
+
+  // Reads the process state from procfs
+  #[cfg(target_os = "linux")]
+  #[tokio::test]
+  async fn command_is_killed_on_timeout() {
+    let pid_file = std::env::temp_dir().join(format!("provider-command-{}.pid", uuid::Uuid::new_v4()));
+    let provider = provider(Duration::from_millis(500));
+    assert!(provider.retrieve_lyrics("slow", "Artist", pid_file.to_str().unwrap(), 180.0).await.is_err());
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    std::fs::remove_file(&pid_file).unwrap();
+
+    // The killed process is gone, or left as a zombie until it is reaped
+    let stat_file = format!("/proc/{}/stat", pid.trim());
+    let mut is_running = true;
+    for _ in 0..50 {
+      is_running = std::fs::read_to_string(&stat_file)
+        .is_ok_and(|stat| stat.rsplit(')').next().is_some_and(|rest| !rest.trim_start().starts_with('Z')));
+      if !is_running {
+        break;
+      }
+      tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(!is_running);
+  }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+use anyhow::{Context, Result};
+use axum::async_trait;
+use crate::{
+  lrc::{self, lint::{parse_length, METADATA_TAGS}},
+  providers::LyricsProvider,
+  queue::ScrapedData,
+  utils::prepare_input,
+};
+
+// Same tolerance as when looking up existing tracks by metadata
+const MAX_DURATION_DIFFERENCE: f64 = 2.0;
+
+struct LocalFile {
+  path: PathBuf,
+  // From the [length:] tag, if the file has one
+  duration: Option<f64>,
+  synced: bool,
+}
+
+// Answers from a directory of .lrc and .txt files laid out as <artist>/<album>/<title>.lrc,
+// indexed once at startup
+pub struct LocalProvider {
+  index: HashMap<(String, String, String), Vec<LocalFile>>,
+}
+
+impl LocalProvider {
+  pub fn new(directory: &Path) -> Result<Self> {
+    let mut index: HashMap<(String, String, String), Vec<LocalFile>> = HashMap::new();
+
+    for path in list_files(directory)? {
+      let Some(key) = index_key(directory, &path) else {
+        continue;
+      };
+      let synced = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("lrc"));
+      let duration = if synced {
+        match fs::read_to_string(&path) {
+          Ok(content) => lrc::parse(&content).tag("length").and_then(parse_length),
+          Err(err) => {
+            tracing::warn!(message = "skipping unreadable lyrics file", path = path.display().to_string(), error = err.to_string());
+            continue;
+          },
+        }
+      } else {
+        None
+      };
+
+      index.entry(key).or_default().push(LocalFile { path, duration, synced });
+    }
+
+    tracing::info!(message = "indexed local lyrics files", directory = directory.display().to_string(), tracks_count = index.len());
+
+    Ok(Self { index })
+  }
+
+  fn find(&self, track_name: &str, artist_name: &str, album_name: &str, duration: f64) -> Option<&LocalFile> {
+    let key = (prepare_input(artist_name), prepare_input(album_name), prepare_input(track_name));
+
+    // Files without a known length are accepted for any duration, but synced and timed files win
+    self.index.get(&key)?
+      .iter()
+      .filter(|file| file.duration.filter(|length| (length - duration).abs() > MAX_DURATION_DIFFERENCE).is_none())
+      .max_by_key(|file| (file.synced, file.duration.is_some()))
+  }
+}
+
+#[async_trait]
+impl LyricsProvider for LocalProvider {
+  fn name(&self) -> &str {
+    "local"
+  }
+
+  async fn retrieve_lyrics(&self, track_name: &str, artist_name: &str, album_name: &str, duration: f64) -> Result<Option<ScrapedData>> {
+    let Some(file) = self.find(track_name, artist_name, album_name, duration) else {
+      return Ok(None);
+    };
+
+    let content = tokio::fs::read_to_string(&file.path).await
+      .with_context(|| format!("failed to read {}", file.path.display()))?;
+
+    if !file.synced {
+      let plain_lyrics = Some(content.trim().to_owned()).filter(|lyrics| !lyrics.is_empty());
+      return Ok(plain_lyrics.map(|plain_lyrics| ScrapedData {
+        plain_lyrics: Some(plain_lyrics),
+        synced_lyrics: None,
+        instrumental: false,
+      }));
+    }
+
+    let mut lrc = lrc::parse(&content);
+    if lrc.is_instrumental() {
+      return Ok(Some(ScrapedData { plain_lyrics: None, synced_lyrics: None, instrumental: true }));
+    }
+    if !lrc.has_timestamps() {
+      return Ok(None);
+    }
+
+    // Store the lyrics the same way published lyrics are stored
+    lrc.bake_offset();
+    lrc.remove_tags(&METADATA_TAGS);
+    if lrc.is_enhanced() {
+      lrc = lrc.to_line_level();
+    }
+
+    Ok(Some(ScrapedData {
+      plain_lyrics: Some(lrc.to_plain()),
+      synced_lyrics: Some(lrc.to_string()),
+      instrumental: false,
+    }))
+  }
+}
+
+fn list_files(directory: &Path) -> Result<Vec<PathBuf>> {
+  let mut files = vec![];
+  let mut directories = vec![directory.to_path_buf()];
+
+  while let Some(directory) = directories.pop() {
+    let entries = fs::read_dir(&directory)
+      .with_context(|| format!("failed to read directory {}", directory.display()))?;
+
+    for entry in entries {
+      let path = entry?.path();
+      if path.is_dir() {
+        directories.push(path);
+      } else {
+        files.push(path);
+      }
+    }
+  }
+
+  Ok(files)
+}
+
+// (artist, album, title) from <artist>/<album>/<title>.(lrc|txt), relative to the root directory
+fn index_key(directory: &Path, path: &Path) -> Option<(String, String, String)> {
+  let extension = path.extension()?.to_str()?.to_lowercase();
+  if extension != "lrc" && extension != "txt" {
+    return None;
+  }
+
+  let relative_path = path.strip_prefix(directory).ok()?;
+  let components = relative_path.iter().map(|component| component.to_str()).collect::<Option<Vec<&str>>>()?;
+  let [artist_name, album_name, _] = components.as_slice() else {
+    return None;
+  };
+  let track_name = path.file_stem()?.to_str()?;
+
+  Some((prepare_input(artist_name), prepare_input(album_name), prepare_input(track_name)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Lyrics directory that is removed again when the test ends
+  struct LyricsDirectory(PathBuf);
+
+  impl LyricsDirectory {
+    fn new(files: &[(&str, &str)]) -> Self {
+      let root = std::env::temp_dir().join(format!("lrclib-local-{}", uuid::Uuid::new_v4()));
+      for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+      }
+      Self(root)
+    }
+  }
+
+  impl Drop for LyricsDirectory {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
+    }
+  }
+
+  #[tokio::test]
+  async fn names_are_normalized_like_track_lookups() {
+    let directory = LyricsDirectory::new(&[
+      ("The Artist/Some Album/Song Title.lrc", "[ti:Song Title]\n[00:01.00]Hello\n[00:02.00]World"),
+      ("The Artist/Song Title.lrc", "[00:01.00]Not in an album directory"),
+      ("The Artist/Some Album/Song Title.md", "Not lyrics"),
+    ]);
+    let provider = LocalProvider::new(&directory.0).unwrap();
+
+    let data = provider.retrieve_lyrics("song title", "THE ARTIST", "Some  Album", 180.0).await.unwrap().unwrap();
+    assert_eq!(data.synced_lyrics.as_deref(), Some("[00:01.00]Hello\n[00:02.00]World"));
+    assert_eq!(data.plain_lyrics.as_deref(), Some("Hello\nWorld"));
+
+    assert!(provider.retrieve_lyrics("Song Title", "The Artist", "Other Album", 180.0).await.unwrap().is_none());
+    assert_eq!(provider.index.len(), 1);
+  }
+
+  #[tokio::test]
+  async fn length_tag_must_be_within_two_seconds() {
+    let directory = LyricsDirectory::new(&[
+      ("Artist/Album/Song.lrc", "[length:03:00]\n[00:01.00]Hello"),
+    ]);
+    let provider = LocalProvider::new(&directory.0).unwrap();
+
+    assert!(provider.retrieve_lyrics("Song", "Artist", "Album", 181.5).await.unwrap().is_some());
+    assert!(provider.retrieve_lyrics("Song", "Artist", "Album", 178.5).await.unwrap().is_some());
+    assert!(provider.retrieve_lyrics("Song", "Artist", "Album", 182.5).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn synced_file_wins_over_plain_text() {
+    let directory = LyricsDirectory::new(&[
+      ("Artist/Album/Song.txt", "Plain only"),
+      ("Artist/Album/Song.lrc", "[00:01.00]Synced"),
+      ("Artist/Album/Other.txt", "Plain only\n"),
+    ]);
+    let provider = LocalProvider::new(&directory.0).unwrap();
+
+    let data = provider.retrieve_lyrics("Song", "Artist", "Album", 180.0).await.unwrap().unwrap();
+    assert_eq!(data.synced_lyrics.as_deref(), Some("[00:01.00]Synced"));
+
+    let data = provider.retrieve_lyrics("Other", "Artist", "Album", 180.0).await.unwrap().unwrap();
+    assert_eq!(data.plain_lyrics.as_deref(), Some("Plain only"));
+    assert!(data.synced_lyrics.is_none());
+  }
+
+  #[tokio::test]
+  async fn plain_text_is_used_when_the_synced_length_does_not_match() {
+    let directory = LyricsDirectory::new(&[
+      ("Artist/Album/Song.txt", "Plain only"),
+      ("Artist/Album/Song.lrc", "[length:4:00]\n[00:01.00]Synced"),
+    ]);
+    let provider = LocalProvider::new(&directory.0).unwrap();
+
+    let data = provider.retrieve_lyrics("Song", "Artist", "Album", 180.0).await.unwrap().unwrap();
+    assert_eq!(data.plain_lyrics.as_deref(), Some("Plain only"));
+  }
+}
@@ -1,14 +1,23 @@
 use anyhow::Result;
-use crate::queue::ScrapedData;
+use axum::async_trait;
+use crate::{providers::LyricsProvider, queue::ScrapedData};
 
+#[derive(Default)]
 pub struct NoopProvider {}
 
 impl NoopProvider {
   pub fn new() -> Self {
     Self {}
   }
+}
+
+#[async_trait]
+impl LyricsProvider for NoopProvider {
+  fn name(&self) -> &str {
+    "noop"
+  }
 
-  pub async fn retrieve_lyrics(&mut self, _track_name: &str, _artist_name: &str, _album_name: &str, _duration: f64) -> Result<Option<ScrapedData>> {
+  async fn retrieve_lyrics(&self, _track_name: &str, _artist_name: &str, _album_name: &str, _duration: f64) -> Result<Option<ScrapedData>> {
     Ok(None)
   }
 }
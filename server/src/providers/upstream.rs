@@ -0,0 +1,150 @@
+use std::time::Duration;
+use anyhow::{bail, Context, Result};
+use axum::async_trait;
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+use crate::{providers::LyricsProvider, queue::ScrapedData};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpstreamTrack {
+  plain_lyrics: Option<String>,
+  synced_lyrics: Option<String>,
+  #[serde(default)]
+  instrumental: bool,
+}
+
+// Looks up missing tracks on another LRCLIB-compatible server through its /api/get endpoint
+pub struct UpstreamProvider {
+  name: String,
+  get_url: Url,
+  client: Client,
+  // Caps the requests in flight to the upstream across all queue workers
+  semaphore: Semaphore,
+}
+
+impl UpstreamProvider {
+  pub fn new(name: &str, base_url: &str, timeout: Duration, max_concurrent_requests: usize) -> Result<Self> {
+    // Without the trailing slash, joining would replace the last segment of the base path
+    let get_url = Url::parse(&format!("{}/", base_url.trim_end_matches('/')))
+      .and_then(|base_url| base_url.join("api/get"))
+      .with_context(|| format!("invalid upstream url: {}", base_url))?;
+
+    let client = Client::builder()
+      .timeout(timeout)
+      .user_agent(concat!("LRCLIB mirror v", env!("CARGO_PKG_VERSION")))
+      .build()?;
+
+    Ok(Self {
+      name: name.to_owned(),
+      get_url,
+      client,
+      semaphore: Semaphore::new(max_concurrent_requests.max(1)),
+    })
+  }
+}
+
+#[async_trait]
+impl LyricsProvider for UpstreamProvider {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  async fn retrieve_lyrics(&self, track_name: &str, artist_name: &str, album_name: &str, duration: f64) -> Result<Option<ScrapedData>> {
+    let _permit = self.semaphore.acquire().await?;
+
+    let response = self.client
+      .get(self.get_url.clone())
+      .query(&[
+        ("track_name", track_name),
+        ("artist_name", artist_name),
+        ("album_name", album_name),
+        ("duration", &duration.to_string()),
+      ])
+      .send()
+      .await?;
+
+    match response.status() {
+      // The upstream does not have the track, or is not allowed to serve it
+      StatusCode::NOT_FOUND | StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS => return Ok(None),
+      status if !status.is_success() => bail!("upstream {} answered with status {}", self.name, status),
+      _ => {},
+    }
+
+    let track = response.json::<UpstreamTrack>().await?;
+    if !track.instrumental && track.plain_lyrics.is_none() && track.synced_lyrics.is_none() {
+      return Ok(None);
+    }
+
+    Ok(Some(ScrapedData {
+      plain_lyrics: track.plain_lyrics,
+      synced_lyrics: track.synced_lyrics,
+      instrumental: track.instrumental,
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use axum::{extract::Query, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+  use serde_json::json;
+  use super::*;
+
+  // Stand-in upstream that answers based on the requested track name
+  async fn get_lyrics(Query(params): Query<HashMap<String, String>>) -> impl IntoResponse {
+    match params.get("track_name").map(String::as_str) {
+      Some("hit") => Json(json!({
+        "trackName": "hit",
+        "plainLyrics": "a",
+        "syncedLyrics": "[00:01.00]a",
+        "instrumental": false,
+      })).into_response(),
+      Some("blocked") => StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS.into_response(),
+      Some("slow") => {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        StatusCode::NOT_FOUND.into_response()
+      },
+      _ => StatusCode::NOT_FOUND.into_response(),
+    }
+  }
+
+  async fn start_upstream() -> UpstreamProvider {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = Router::new().route("/mirror/api/get", get(get_lyrics));
+    tokio::spawn(async move {
+      axum::serve(listener, app).await.unwrap();
+    });
+
+    UpstreamProvider::new("upstream", &format!("http://{}/mirror", address), Duration::from_millis(200), 2).unwrap()
+  }
+
+  #[tokio::test]
+  async fn found_track_is_returned() {
+    let provider = start_upstream().await;
+    let data = provider.retrieve_lyrics("hit", "Artist", "Album", 180.0).await.unwrap().unwrap();
+    assert_eq!(data.plain_lyrics.as_deref(), Some("a"));
+    assert_eq!(data.synced_lyrics.as_deref(), Some("[00:01.00]a"));
+    assert!(!data.instrumental);
+  }
+
+  #[tokio::test]
+  async fn missing_track_is_not_found() {
+    let provider = start_upstream().await;
+    assert!(provider.retrieve_lyrics("missing", "Artist", "Album", 180.0).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn taken_down_track_is_not_found() {
+    let provider = start_upstream().await;
+    assert!(provider.retrieve_lyrics("blocked", "Artist", "Album", 180.0).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn timeout_is_an_error_to_retry() {
+    let provider = start_upstream().await;
+    assert!(provider.retrieve_lyrics("slow", "Artist", "Album", 180.0).await.is_err());
+  }
+}
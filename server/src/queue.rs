@@ -1,92 +1,170 @@
 use std::sync::Arc;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rusqlite::Connection;
-use crate::providers::noop::NoopProvider;
-use crate::repositories::{lyrics_repository, track_repository};
-use crate::entities::missing_track::MissingTrack;
+use chrono::Utc;
+use crate::repositories::{lyrics_repository, missing_track_repository, track_repository};
+use crate::entities::missing_track::{MissingTrack, MissingTrackCounts, QueuedMissingTrack};
 use crate::AppState;
+use serde::Deserialize;
 
-#[derive(Debug)]
+// Claims older than this are taken over by another worker, their worker is assumed to be gone
+pub const CLAIM_LEASE_MINUTES: i64 = 10;
+// Give up on a missing track after this many failed lookups
+pub const MAX_ATTEMPTS: i64 = 5;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ScrapedData {
   pub plain_lyrics: Option<String>,
   pub synced_lyrics: Option<String>,
+  #[serde(default)]
   pub instrumental: bool,
 }
 
 pub async fn start_queue(workers_count: u8, state: Arc<AppState>) {
-  // Do not start queue if the workers_count is equal or smaller than zero
-  if workers_count <= 0 {
+  // Do not start queue if the workers_count is equal to zero
+  if workers_count == 0 {
     return
   }
 
-  for _ in 0..workers_count {
+  // Several processes can share the queue, so claims are attributed to a worker of this process
+  let process_id = uuid::Uuid::new_v4();
+
+  for index in 0..workers_count {
     let state_clone = Arc::clone(&state);
+    let worker_id = format!("{}-{}", process_id, index);
 
     tokio::spawn(async move {
-      worker(state_clone).await;
+      worker(state_clone, worker_id).await;
     });
   }
+
+  // Remaining jobs, counted on a timer rather than for every job since it scans the whole queue
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+    loop {
+      interval.tick().await;
+      match get_counts(&state) {
+        Ok(counts) => tracing::info!(
+          message = "missing tracks in queue",
+          pending_count = counts.pending_count,
+          claimed_count = counts.claimed_count,
+          failed_count = counts.failed_count,
+          queue = true,
+        ),
+        Err(err) => tracing::error!(message = "failed to count missing tracks", error = err.to_string(), queue = true),
+      }
+    }
+  });
 }
 
-async fn worker(state: Arc<AppState>) {
-  let mut provider = NoopProvider::new();
-  loop {
-    let maybe_missing_track = get_next_track(&state).await;
+pub fn get_counts(state: &Arc<AppState>) -> Result<MissingTrackCounts> {
+  let mut conn = state.pool.get()?;
+  missing_track_repository::get_counts(chrono::Duration::minutes(CLAIM_LEASE_MINUTES), MAX_ATTEMPTS, &mut conn)
+}
 
-    if let Some(missing_track) = maybe_missing_track {
-      process_track(&state, &mut provider, missing_track).await;
-    } else {
-      tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+async fn worker(state: Arc<AppState>, worker_id: String) {
+  loop {
+    match get_next_track(&state, &worker_id).await {
+      Ok(Some(queued_track)) => process_track(&state, queued_track).await,
+      Ok(None) => tokio::time::sleep(std::time::Duration::from_millis(500)).await,
+      Err(err) => {
+        tracing::error!(message = "failed to claim a missing track", error = err.to_string(), queue = true);
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+      },
     }
   }
 }
 
-async fn get_next_track(state: &Arc<AppState>) -> Option<MissingTrack> {
-  state.queue.pop()
+async fn get_next_track(state: &Arc<AppState>, worker_id: &str) -> Result<Option<QueuedMissingTrack>> {
+  let mut conn = state.pool.get()?;
+  missing_track_repository::claim_next(worker_id, chrono::Duration::minutes(CLAIM_LEASE_MINUTES), MAX_ATTEMPTS, &mut conn)
 }
 
-async fn process_track(state: &Arc<AppState>, provider: &mut NoopProvider, missing_track: MissingTrack) {
-  let maybe_data = provider.retrieve_lyrics(
-    &missing_track.name,
-    &missing_track.artist_name,
-    &missing_track.album_name,
-    missing_track.duration,
-  ).await;
+// Try the providers in order and keep the first lyrics found
+async fn process_track(state: &Arc<AppState>, queued_track: QueuedMissingTrack) {
+  let missing_track = queued_track.track;
+  let mut has_failed = false;
 
-  match maybe_data {
-    Ok(data) => {
-      process_lyrics_result(&missing_track, data, state).await;
-    },
-    Err(err) => {
-      tracing::error!(
-        message = format!("error while finding lyrics"),
+  for provider in &state.providers {
+    let maybe_data = provider.retrieve_lyrics(
+      &missing_track.name,
+      &missing_track.artist_name,
+      &missing_track.album_name,
+      missing_track.duration,
+    ).await;
+
+    match maybe_data {
+      Ok(Some(data)) => {
+        process_lyrics_result(&missing_track, Some((provider.name(), data)), state).await;
+        acknowledge_track(queued_track.id, state);
+        return;
+      },
+      Ok(None) => {},
+      Err(err) => {
+        tracing::error!(
+          message = format!("error while finding lyrics"),
+          provider = provider.name(),
+          track_name = missing_track.name,
+          artist_name = missing_track.artist_name,
+          album_name = missing_track.album_name,
+          duration = missing_track.duration,
+          error = err.to_string(),
+          queue = true,
+        );
+        has_failed = true;
+      },
+    }
+  }
+
+  if has_failed {
+    // Give the track back to the queue, a provider might be able to answer later.
+    // The delay grows with each attempt so an unreachable provider is not retried in a busy loop.
+    // A track that used up its attempts is not claimed again and stays until it is cleaned up.
+    if queued_track.attempts >= MAX_ATTEMPTS {
+      tracing::warn!(
+        message = "giving up on missing track",
         track_name = missing_track.name,
         artist_name = missing_track.artist_name,
         album_name = missing_track.album_name,
         duration = missing_track.duration,
-        error = err.to_string(),
         queue = true,
       );
+    }
+    let available_at = Utc::now() + chrono::Duration::minutes(queued_track.attempts * queued_track.attempts);
+    let result = state.pool.get().map_err(anyhow::Error::from)
+      .and_then(|mut conn| missing_track_repository::release(queued_track.id, available_at, &mut conn));
 
-      // Push the track back to the queue
-      let _ = state.queue.push(missing_track);
-    },
+    if let Err(err) = result {
+      tracing::error!(message = "failed to release a missing track", error = err.to_string(), queue = true);
+    }
+  } else {
+    process_lyrics_result(&missing_track, None, state).await;
+    acknowledge_track(queued_track.id, state);
   }
 }
 
-async fn process_lyrics_result(missing_track: &MissingTrack, data: Option<ScrapedData>, state: &Arc<AppState>) {
+fn acknowledge_track(id: i64, state: &Arc<AppState>) {
+  let result = state.pool.get().map_err(anyhow::Error::from)
+    .and_then(|mut conn| missing_track_repository::delete_one(id, &mut conn));
+
+  if let Err(err) = result {
+    tracing::error!(message = "failed to remove a missing track from the queue", error = err.to_string(), queue = true);
+  }
+}
+
+async fn process_lyrics_result(missing_track: &MissingTrack, data: Option<(&str, ScrapedData)>, state: &Arc<AppState>) {
   let mut conn = state.pool.get().unwrap();
-  let remaining_jobs = get_remaining_jobs(&state).await;
 
-  if let Some(data) = data {
-    match add_found(missing_track, &data, &mut conn).await {
+  if let Some((source, data)) = data {
+    match add_found(missing_track, &data, source, &mut conn).await {
       Ok(_) => tracing::info!(
         message = format!("added new lyrics"),
+        source = source,
         track_name = missing_track.name,
         artist_name = missing_track.artist_name,
         album_name = missing_track.album_name,
         duration = missing_track.duration,
-        remaining_jobs = remaining_jobs,
         queue = true,
       ),
       Err(err) => tracing::error!(
@@ -95,7 +173,6 @@ async fn process_lyrics_result(missing_track: &MissingTrack, data: Option<Scrape
         artist_name = missing_track.artist_name,
         album_name = missing_track.album_name,
         duration = missing_track.duration,
-        remaining_jobs = remaining_jobs,
         error = err.to_string(),
         queue = true,
       ),
@@ -107,29 +184,47 @@ async fn process_lyrics_result(missing_track: &MissingTrack, data: Option<Scrape
       artist_name = missing_track.artist_name,
       album_name = missing_track.album_name,
       duration = missing_track.duration,
-      remaining_jobs = remaining_jobs,
       queue = true,
     );
   }
 }
 
-async fn add_found(missing_track: &MissingTrack, data: &ScrapedData, conn: &mut Connection) -> Result<()> {
+async fn add_found(missing_track: &MissingTrack, data: &ScrapedData, source: &str, conn: &mut Connection) -> Result<()> {
   let mut tx = conn.transaction()?;
 
-  let track_id = track_repository::add_one_tx(
-    &missing_track.name.trim(),
-    &missing_track.artist_name.trim(),
-    &missing_track.album_name.trim(),
+  // The track may have been added since it was queued, e.g. by a publish
+  let existing_track = track_repository::get_track_id_by_metadata_tx(
+    missing_track.name.trim(),
+    missing_track.artist_name.trim(),
+    missing_track.album_name.trim(),
     missing_track.duration,
     &mut tx,
   )?;
 
+  let track_id = match existing_track {
+    Some(track_id) => {
+      let is_taken_down = track_repository::get_track_tx(track_id, &mut tx)?.is_some_and(|track| track.taken_down);
+      if is_taken_down {
+        bail!("track {} is taken down", track_id);
+      }
+      track_id
+    },
+    None => track_repository::add_one_tx(
+      missing_track.name.trim(),
+      missing_track.artist_name.trim(),
+      missing_track.album_name.trim(),
+      missing_track.duration,
+      &mut tx,
+    )?
+  };
+
   lyrics_repository::add_one_tx(
     &data.plain_lyrics,
     &data.synced_lyrics,
+    &None,
     track_id,
     data.instrumental,
-    &None,
+    &Some(source.to_owned()),
     &mut tx,
   )?;
 
@@ -138,6 +233,50 @@ async fn add_found(missing_track: &MissingTrack, data: &ScrapedData, conn: &mut
   Ok(())
 }
 
-async fn get_remaining_jobs(state: &Arc<AppState>) -> usize {
-  state.queue.len()
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::db::test_connection;
+
+  fn missing_track(album_name: &str) -> MissingTrack {
+    MissingTrack { name: "Song".to_owned(), artist_name: "Artist".to_owned(), album_name: album_name.to_owned(), duration: 201.0 }
+  }
+
+  fn data() -> ScrapedData {
+    ScrapedData { plain_lyrics: Some("found".to_owned()), synced_lyrics: None, instrumental: false }
+  }
+
+  fn tracks_count(conn: &mut Connection) -> i64 {
+    conn.query_row("SELECT COUNT(*) FROM tracks", [], |row| row.get(0)).unwrap()
+  }
+
+  #[tokio::test]
+  async fn lyrics_are_attached_to_an_existing_track() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    add_found(&missing_track(" album "), &data(), "local", &mut conn).await.unwrap();
+    add_found(&missing_track("Other Album"), &data(), "local", &mut conn).await.unwrap();
+
+    assert_eq!(tracks_count(&mut conn), 2);
+    let revisions = lyrics_repository::get_lyrics_by_track_id(track_id, &mut conn).unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].source.as_deref(), Some("local"));
+    assert_eq!(track_repository::get_track(track_id, &mut conn).unwrap().unwrap().last_lyrics_id, Some(revisions[0].id));
+  }
+
+  #[tokio::test]
+  async fn taken_down_track_gets_no_lyrics() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    track_repository::set_taken_down_tx(track_id, true, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    assert!(add_found(&missing_track("Album"), &data(), "local", &mut conn).await.is_err());
+    assert!(lyrics_repository::get_lyrics_by_track_id(track_id, &mut conn).unwrap().is_empty());
+    assert_eq!(tracks_count(&mut conn), 1);
+  }
 }
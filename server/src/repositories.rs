@@ -1,3 +1,8 @@
 pub mod track_repository;
 pub mod lyrics_repository;
 pub mod missing_track_repository;
+pub mod lyrics_revert_repository;
+pub mod lyrics_vote_repository;
+pub mod flag_repository;
+pub mod audit_log_repository;
+pub mod takedown_repository;
@@ -0,0 +1,178 @@
+use anyhow::Result;
+use rusqlite::{params_from_iter, Connection, Transaction};
+use indoc::indoc;
+use chrono::prelude::*;
+use serde_json::Value;
+use crate::entities::audit_log::{AuditAction, AuditLog, AuditLogFilter};
+
+// created_at is stored and filtered as text in this format, which sorts chronologically for UTC
+// timestamps: the fraction is left-aligned and "+" sorts before any digit when it is left out.
+const TIMESTAMP_FORMAT: &str = "%F %T%.f%:z";
+
+pub fn add_one_tx(
+  actor: &str,
+  action: AuditAction,
+  track_id: Option<i64>,
+  lyrics_id: Option<i64>,
+  before: Option<&Value>,
+  after: Option<&Value>,
+  conn: &mut Transaction,
+) -> Result<i64> {
+  let now = Utc::now();
+  let query = indoc! {"
+    INSERT INTO audit_logs (
+      actor,
+      action,
+      track_id,
+      lyrics_id,
+      before,
+      after,
+      created_at
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?)
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row_id = statement.insert(
+    (
+      actor,
+      action.as_str(),
+      track_id,
+      lyrics_id,
+      before.map(|before| before.to_string()),
+      after.map(|after| after.to_string()),
+      now.format(TIMESTAMP_FORMAT).to_string(),
+    )
+  )?;
+  Ok(row_id)
+}
+
+// Newest entries first
+pub fn get_audit_logs(filter: &AuditLogFilter, limit: i64, offset: i64, conn: &mut Connection) -> Result<Vec<AuditLog>> {
+  let mut where_clauses = vec!["1 = 1".to_owned()];
+  let mut params: Vec<rusqlite::types::Value> = vec![];
+
+  if let Some(ref actor) = filter.actor {
+    where_clauses.push("audit_logs.actor = ?".to_owned());
+    params.push(actor.to_owned().into());
+  }
+  if let Some(action) = filter.action {
+    where_clauses.push("audit_logs.action = ?".to_owned());
+    params.push(action.as_str().to_owned().into());
+  }
+  if let Some(track_id) = filter.track_id {
+    where_clauses.push("audit_logs.track_id = ?".to_owned());
+    params.push(track_id.into());
+  }
+  if let Some(lyrics_id) = filter.lyrics_id {
+    where_clauses.push("audit_logs.lyrics_id = ?".to_owned());
+    params.push(lyrics_id.into());
+  }
+  if let Some(since) = filter.since {
+    where_clauses.push("audit_logs.created_at >= ?".to_owned());
+    params.push(since.format(TIMESTAMP_FORMAT).to_string().into());
+  }
+  if let Some(until) = filter.until {
+    where_clauses.push("audit_logs.created_at < ?".to_owned());
+    params.push(until.format(TIMESTAMP_FORMAT).to_string().into());
+  }
+  params.push(limit.into());
+  params.push(offset.into());
+
+  let query = format!(
+    "SELECT * FROM audit_logs WHERE {where_clause} ORDER BY audit_logs.id DESC LIMIT ? OFFSET ?",
+    where_clause = where_clauses.join(" AND "),
+  );
+
+  let mut statement = conn.prepare(&query)?;
+  let rows = statement.query_map(
+    params_from_iter(params.iter()),
+    |row| {
+      let before: Option<String> = row.get("before")?;
+      let after: Option<String> = row.get("after")?;
+
+      Ok(AuditLog {
+        id: row.get("id")?,
+        actor: row.get("actor")?,
+        action: row.get("action")?,
+        track_id: row.get("track_id")?,
+        lyrics_id: row.get("lyrics_id")?,
+        before: before.and_then(|before| serde_json::from_str(&before).ok()),
+        after: after.and_then(|after| serde_json::from_str(&after).ok()),
+        created_at: row.get("created_at")?,
+      })
+    }
+  )?;
+  Ok(rows.collect::<rusqlite::Result<Vec<AuditLog>>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::db::test_connection;
+
+  // Entries are append-only, so they are inserted with a fixed created_at instead of updated
+  fn add_log_at(created_at: &str, conn: &mut Connection) -> i64 {
+    conn.execute(
+      "INSERT INTO audit_logs (actor, action, created_at) VALUES ('mod', 'hide_lyrics', ?)",
+      [created_at],
+    ).unwrap();
+    conn.last_insert_rowid()
+  }
+
+  fn filter(since: Option<&str>, until: Option<&str>) -> AuditLogFilter {
+    AuditLogFilter {
+      actor: None,
+      action: None,
+      track_id: None,
+      lyrics_id: None,
+      since: since.map(|since| since.parse().unwrap()),
+      until: until.map(|until| until.parse().unwrap()),
+    }
+  }
+
+  fn log_ids(filter: &AuditLogFilter, conn: &mut Connection) -> Vec<i64> {
+    get_audit_logs(filter, 50, 0, conn).unwrap().iter().map(|log| log.id).collect()
+  }
+
+  #[test]
+  fn created_at_is_stored_in_the_timestamp_format() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let id = add_one_tx("mod", AuditAction::HideLyrics, None, None, None, None, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    let created_at: String = conn.query_row("SELECT created_at FROM audit_logs WHERE id = ?", [id], |row| row.get(0)).unwrap();
+    let parsed = DateTime::parse_from_str(&created_at, TIMESTAMP_FORMAT).unwrap();
+    assert_eq!(parsed.format(TIMESTAMP_FORMAT).to_string(), created_at);
+    assert!(created_at.ends_with("+00:00"));
+
+    let logs = get_audit_logs(&filter(None, None), 50, 0, &mut conn).unwrap();
+    assert_eq!(logs[0].created_at, parsed);
+  }
+
+  #[test]
+  fn since_is_inclusive_and_until_is_exclusive() {
+    let mut conn = test_connection();
+    let first = add_log_at("2026-01-01 09:59:59.999+00:00", &mut conn);
+    let second = add_log_at("2026-01-01 10:00:00+00:00", &mut conn);
+    let third = add_log_at("2026-01-01 10:00:00.500+00:00", &mut conn);
+    let fourth = add_log_at("2026-01-01 11:00:00+00:00", &mut conn);
+
+    assert_eq!(log_ids(&filter(None, None), &mut conn), vec![fourth, third, second, first]);
+    assert_eq!(log_ids(&filter(Some("2026-01-01T10:00:00Z"), None), &mut conn), vec![fourth, third, second]);
+    assert_eq!(log_ids(&filter(None, Some("2026-01-01T11:00:00Z")), &mut conn), vec![third, second, first]);
+    assert_eq!(
+      log_ids(&filter(Some("2026-01-01T10:00:00.250Z"), Some("2026-01-01T10:00:00.500001Z")), &mut conn),
+      vec![third],
+    );
+  }
+
+  #[test]
+  fn logs_are_paged_newest_first() {
+    let mut conn = test_connection();
+    let ids: Vec<i64> = (0..5).map(|_| add_log_at("2026-01-01 10:00:00+00:00", &mut conn)).collect();
+
+    let page = get_audit_logs(&filter(None, None), 2, 1, &mut conn).unwrap();
+    assert_eq!(page.iter().map(|log| log.id).collect::<Vec<i64>>(), vec![ids[3], ids[2]]);
+  }
+}
@@ -0,0 +1,199 @@
+use anyhow::Result;
+use rusqlite::{Connection, Row, Transaction};
+use indoc::indoc;
+use chrono::prelude::*;
+use crate::entities::flag::{Flag, FlagGroup, FlagReason, FlagStatus};
+
+fn row_to_flag(row: &Row) -> rusqlite::Result<Flag> {
+  let status: String = row.get("status")?;
+  let reason: String = row.get("reason")?;
+
+  Ok(Flag {
+    id: row.get("id")?,
+    lyrics_id: row.get("lyrics_id")?,
+    reason: FlagReason::parse(&reason).unwrap_or(FlagReason::Other),
+    details: row.get("details")?,
+    suggested_offset: row.get("suggested_offset")?,
+    status: FlagStatus::parse(&status).unwrap_or(FlagStatus::Open),
+    reviewer: row.get("reviewer")?,
+    resolution_notes: row.get("resolution_notes")?,
+    created_at: row.get("created_at")?,
+    resolved_at: row.get("resolved_at")?,
+  })
+}
+
+// Lyrics revisions with flags in the given status, most flagged first
+pub fn get_flag_groups(status: FlagStatus, limit: i64, offset: i64, conn: &mut Connection) -> Result<Vec<FlagGroup>> {
+  let query = indoc! {"
+    SELECT
+      flags.lyrics_id,
+      lyrics.track_id,
+      lyrics.hidden,
+      lyrics.auto_hidden,
+      tracks.name,
+      tracks.artist_name,
+      tracks.album_name,
+      COUNT(flags.id) AS flags_count,
+      MIN(flags.created_at) AS first_flagged_at,
+      MAX(flags.created_at) AS last_flagged_at
+    FROM
+      flags
+      JOIN lyrics ON flags.lyrics_id = lyrics.id
+      JOIN tracks ON lyrics.track_id = tracks.id
+    WHERE
+      flags.status = ?
+    GROUP BY
+      flags.lyrics_id
+    ORDER BY
+      flags_count DESC,
+      last_flagged_at DESC
+    LIMIT ? OFFSET ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let rows = statement.query_map(
+    (status.as_str(), limit, offset),
+    |row| {
+      Ok(FlagGroup {
+        lyrics_id: row.get("lyrics_id")?,
+        track_id: row.get("track_id")?,
+        track_name: row.get("name")?,
+        artist_name: row.get("artist_name")?,
+        album_name: row.get("album_name")?,
+        hidden: row.get("hidden")?,
+        auto_hidden: row.get("auto_hidden")?,
+        flags_count: row.get("flags_count")?,
+        first_flagged_at: row.get("first_flagged_at")?,
+        last_flagged_at: row.get("last_flagged_at")?,
+      })
+    }
+  )?;
+  Ok(rows.collect::<rusqlite::Result<Vec<FlagGroup>>>()?)
+}
+
+pub fn get_flags_by_lyrics_id(lyrics_id: i64, status: FlagStatus, conn: &mut Connection) -> Result<Vec<Flag>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      flags
+    WHERE
+      flags.lyrics_id = ?
+      AND flags.status = ?
+    ORDER BY
+      flags.id
+  "};
+  let mut statement = conn.prepare(query)?;
+  let rows = statement.query_map((lyrics_id, status.as_str()), row_to_flag)?;
+  Ok(rows.collect::<rusqlite::Result<Vec<Flag>>>()?)
+}
+
+pub fn get_flags_by_lyrics_id_tx(lyrics_id: i64, status: FlagStatus, conn: &mut Transaction) -> Result<Vec<Flag>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      flags
+    WHERE
+      flags.lyrics_id = ?
+      AND flags.status = ?
+    ORDER BY
+      flags.id
+  "};
+  let mut statement = conn.prepare(query)?;
+  let rows = statement.query_map((lyrics_id, status.as_str()), row_to_flag)?;
+  Ok(rows.collect::<rusqlite::Result<Vec<Flag>>>()?)
+}
+
+// Close all open flags of a lyrics revision. Returns the number of flags resolved.
+pub fn resolve_open_flags_by_lyrics_id_tx(
+  lyrics_id: i64,
+  status: FlagStatus,
+  reviewer: &str,
+  resolution_notes: Option<&str>,
+  conn: &mut Transaction,
+) -> Result<usize> {
+  let now = Utc::now();
+  let query = indoc! {"
+    UPDATE
+      flags
+    SET
+      status = ?,
+      reviewer = ?,
+      resolution_notes = ?,
+      resolved_at = ?
+    WHERE
+      flags.lyrics_id = ?
+      AND flags.status = 'open'
+  "};
+  let mut statement = conn.prepare(query)?;
+  let count = statement.execute((status.as_str(), reviewer, resolution_notes, now, lyrics_id))?;
+  Ok(count)
+}
+
+pub fn delete_by_lyrics_id_tx(lyrics_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    DELETE FROM flags WHERE lyrics_id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute([lyrics_id])?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{db::test_connection, repositories::{lyrics_repository, track_repository}};
+
+  fn add_track(track_name: &str, conn: &mut Connection) -> (i64, i64) {
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx(track_name, "Artist", "Album", 200.0, &mut tx).unwrap();
+    let lyrics_id = lyrics_repository::add_one_tx(&Some("lyrics".to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), &mut tx).unwrap();
+    tx.commit().unwrap();
+    (track_id, lyrics_id)
+  }
+
+  fn flag(track_id: i64, reason: FlagReason, conn: &mut Connection) {
+    track_repository::flag_track_last_lyrics(track_id, reason, Some("details"), None, "challenge", None, conn).unwrap();
+  }
+
+  #[test]
+  fn groups_are_ordered_by_flags_count() {
+    let mut conn = test_connection();
+    let (once_track_id, once_lyrics_id) = add_track("Once", &mut conn);
+    let (twice_track_id, twice_lyrics_id) = add_track("Twice", &mut conn);
+    flag(once_track_id, FlagReason::Spam, &mut conn);
+    flag(twice_track_id, FlagReason::Spam, &mut conn);
+    flag(twice_track_id, FlagReason::OutOfSync, &mut conn);
+
+    let groups = get_flag_groups(FlagStatus::Open, 20, 0, &mut conn).unwrap();
+    assert_eq!(
+      groups.iter().map(|group| (group.lyrics_id, group.track_name.as_deref(), group.flags_count)).collect::<Vec<_>>(),
+      [(twice_lyrics_id, Some("Twice"), 2), (once_lyrics_id, Some("Once"), 1)],
+    );
+    assert!(groups[0].first_flagged_at <= groups[0].last_flagged_at);
+
+    let groups = get_flag_groups(FlagStatus::Open, 1, 1, &mut conn).unwrap();
+    assert_eq!(groups.iter().map(|group| group.lyrics_id).collect::<Vec<_>>(), [once_lyrics_id]);
+
+    let flags = get_flags_by_lyrics_id(twice_lyrics_id, FlagStatus::Open, &mut conn).unwrap();
+    assert_eq!(flags.iter().map(|flag| flag.reason).collect::<Vec<_>>(), [FlagReason::Spam, FlagReason::OutOfSync]);
+  }
+
+  #[test]
+  fn resolved_flags_move_to_their_status() {
+    let mut conn = test_connection();
+    let (track_id, lyrics_id) = add_track("Song", &mut conn);
+    flag(track_id, FlagReason::Spam, &mut conn);
+
+    let mut tx = conn.transaction().unwrap();
+    assert_eq!(resolve_open_flags_by_lyrics_id_tx(lyrics_id, FlagStatus::Rejected, "mod", None, &mut tx).unwrap(), 1);
+    tx.commit().unwrap();
+
+    assert!(get_flag_groups(FlagStatus::Open, 20, 0, &mut conn).unwrap().is_empty());
+    let groups = get_flag_groups(FlagStatus::Rejected, 20, 0, &mut conn).unwrap();
+    assert_eq!(groups.iter().map(|group| group.lyrics_id).collect::<Vec<_>>(), [lyrics_id]);
+    let flags = get_flags_by_lyrics_id(lyrics_id, FlagStatus::Rejected, &mut conn).unwrap();
+    assert_eq!(flags[0].reviewer.as_deref(), Some("mod"));
+    assert!(flags[0].resolved_at.is_some());
+  }
+}
@@ -1,11 +1,77 @@
 use anyhow::Result;
-use rusqlite::{Connection, Transaction};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction};
 use indoc::indoc;
 use chrono::prelude::*;
+use crate::entities::lyrics::Lyrics;
+
+fn row_to_lyrics(row: &Row) -> rusqlite::Result<Lyrics> {
+  Ok(Lyrics {
+    id: row.get("id")?,
+    plain_lyrics: row.get("plain_lyrics")?,
+    synced_lyrics: row.get("synced_lyrics")?,
+    enhanced_lyrics: row.get("enhanced_lyrics")?,
+    track_id: row.get("track_id")?,
+    has_plain_lyrics: row.get::<_, Option<bool>>("has_plain_lyrics")?.unwrap_or_default(),
+    has_synced_lyrics: row.get::<_, Option<bool>>("has_synced_lyrics")?.unwrap_or_default(),
+    instrumental: row.get::<_, Option<bool>>("instrumental")?.unwrap_or_default(),
+    score: row.get("score")?,
+    hidden: row.get("hidden")?,
+    auto_hidden: row.get("auto_hidden")?,
+    taken_down: row.get("taken_down")?,
+    source: row.get("source")?,
+    created_at: row.get("created_at")?,
+    updated_at: row.get("updated_at")?,
+  })
+}
+
+pub fn get_lyrics_by_id(lyrics_id: i64, conn: &mut Connection) -> Result<Option<Lyrics>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      lyrics
+    WHERE
+      lyrics.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row([lyrics_id], row_to_lyrics).optional()?;
+  Ok(row)
+}
+
+pub fn get_lyrics_by_id_tx(lyrics_id: i64, conn: &mut Transaction) -> Result<Option<Lyrics>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      lyrics
+    WHERE
+      lyrics.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row([lyrics_id], row_to_lyrics).optional()?;
+  Ok(row)
+}
+
+pub fn get_lyrics_by_track_id(track_id: i64, conn: &mut Connection) -> Result<Vec<Lyrics>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      lyrics
+    WHERE
+      lyrics.track_id = ?
+    ORDER BY
+      lyrics.id DESC
+  "};
+  let mut statement = conn.prepare(query)?;
+  let rows = statement.query_map([track_id], row_to_lyrics)?;
+  Ok(rows.collect::<rusqlite::Result<Vec<Lyrics>>>()?)
+}
 
 pub fn add_one(
   plain_lyrics: &Option<String>,
   synced_lyrics: &Option<String>,
+  enhanced_lyrics: &Option<String>,
   track_id: i64,
   instrumental: bool,
   source: &Option<String>,
@@ -13,12 +79,14 @@ pub fn add_one(
 ) -> Result<i64> {
   let plain_lyrics = plain_lyrics.as_ref().filter(|s| !s.is_empty());
   let synced_lyrics = synced_lyrics.as_ref().filter(|s| !s.is_empty());
+  let enhanced_lyrics = enhanced_lyrics.as_ref().filter(|s| !s.is_empty());
 
   let now = Utc::now();
   let query = indoc! {"
     INSERT INTO lyrics (
       plain_lyrics,
       synced_lyrics,
+      enhanced_lyrics,
       has_plain_lyrics,
       has_synced_lyrics,
       instrumental,
@@ -27,13 +95,14 @@ pub fn add_one(
       created_at,
       updated_at
     )
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
   "};
   let mut statement = conn.prepare(query)?;
   let row_id = statement.insert(
     (
       plain_lyrics,
       synced_lyrics,
+      enhanced_lyrics,
       plain_lyrics.is_some(),
       synced_lyrics.is_some(),
       instrumental,
@@ -49,6 +118,7 @@ pub fn add_one(
 pub fn add_one_tx(
   plain_lyrics: &Option<String>,
   synced_lyrics: &Option<String>,
+  enhanced_lyrics: &Option<String>,
   track_id: i64,
   instrumental: bool,
   source: &Option<String>,
@@ -56,12 +126,14 @@ pub fn add_one_tx(
 ) -> Result<i64> {
   let plain_lyrics = plain_lyrics.as_ref().filter(|s| !s.is_empty());
   let synced_lyrics = synced_lyrics.as_ref().filter(|s| !s.is_empty());
+  let enhanced_lyrics = enhanced_lyrics.as_ref().filter(|s| !s.is_empty());
 
   let now = Utc::now();
   let query = indoc! {"
     INSERT INTO lyrics (
       plain_lyrics,
       synced_lyrics,
+      enhanced_lyrics,
       has_plain_lyrics,
       has_synced_lyrics,
       instrumental,
@@ -70,13 +142,14 @@ pub fn add_one_tx(
       created_at,
       updated_at
     )
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
   "};
   let mut statement = conn.prepare(query)?;
   let row_id = statement.insert(
     (
       plain_lyrics,
       synced_lyrics,
+      enhanced_lyrics,
       plain_lyrics.is_some(),
       synced_lyrics.is_some(),
       instrumental,
@@ -89,6 +162,73 @@ pub fn add_one_tx(
   Ok(row_id)
 }
 
+// Score that ranks a new revision above every visible revision of the track, so that a fresh
+// publish or a revert is served until it is voted down
+pub fn get_next_top_score_tx(track_id: i64, conn: &mut Transaction) -> Result<i64> {
+  let query = indoc! {"
+    SELECT MAX(score) FROM lyrics WHERE track_id = ? AND NOT hidden
+  "};
+  let mut statement = conn.prepare(query)?;
+  let top_score: Option<i64> = statement.query_row([track_id], |row| row.get(0))?;
+  Ok(top_score.map_or(0, |top_score| (top_score + 1).max(0)))
+}
+
+pub fn set_score_tx(lyrics_id: i64, score: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE lyrics SET score = ? WHERE id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((score, lyrics_id))?;
+  Ok(())
+}
+
+pub fn add_score_tx(lyrics_id: i64, delta: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE lyrics SET score = score + ? WHERE id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((delta, lyrics_id))?;
+  Ok(())
+}
+
+pub fn set_hidden_tx(lyrics_id: i64, hidden: bool, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE lyrics SET hidden = ?, auto_hidden = 0, updated_at = ? WHERE id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((hidden, Utc::now(), lyrics_id))?;
+  Ok(())
+}
+
+// Returns the track id of the deleted lyrics, or None if they did not exist
+pub fn delete_one_tx(lyrics_id: i64, conn: &mut Transaction) -> Result<Option<i64>> {
+  let query = indoc! {"
+    DELETE FROM lyrics WHERE id = ? RETURNING track_id
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row([lyrics_id], |row| row.get("track_id")).optional()?;
+  Ok(row)
+}
+
+pub fn move_to_track_tx(from_track_id: i64, to_track_id: i64, conn: &mut Transaction) -> Result<usize> {
+  let query = indoc! {"
+    UPDATE lyrics SET track_id = ? WHERE track_id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let count = statement.execute((to_track_id, from_track_id))?;
+  Ok(count)
+}
+
+// Taking down a revision also hides it, lifting the takedown leaves it hidden
+pub fn set_taken_down_tx(lyrics_id: i64, taken_down: bool, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE lyrics SET taken_down = ?1, hidden = hidden OR ?1, updated_at = ?2 WHERE id = ?3
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((taken_down, Utc::now(), lyrics_id))?;
+  Ok(())
+}
+
 pub fn get_last_10_mins_lyrics_count(conn: &mut Connection) -> Result<i64> {
   let query = indoc! {"
     SELECT COUNT(*) FROM lyrics
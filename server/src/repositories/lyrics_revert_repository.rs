@@ -0,0 +1,67 @@
+use anyhow::Result;
+use rusqlite::Transaction;
+use indoc::indoc;
+use chrono::prelude::*;
+
+pub fn add_one_tx(
+  track_id: i64,
+  reverted_lyrics_id: Option<i64>,
+  restored_lyrics_id: i64,
+  lyrics_id: i64,
+  actor: &str,
+  reason: &str,
+  conn: &mut Transaction,
+) -> Result<i64> {
+  let now = Utc::now();
+  let query = indoc! {"
+    INSERT INTO lyrics_reverts (
+      track_id,
+      reverted_lyrics_id,
+      restored_lyrics_id,
+      lyrics_id,
+      actor,
+      reason,
+      created_at
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?)
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row_id = statement.insert(
+    (
+      track_id,
+      reverted_lyrics_id,
+      restored_lyrics_id,
+      lyrics_id,
+      actor,
+      reason,
+      now,
+    )
+  )?;
+  Ok(row_id)
+}
+
+pub fn move_to_track_tx(from_track_id: i64, to_track_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE lyrics_reverts SET track_id = ? WHERE track_id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((to_track_id, from_track_id))?;
+  Ok(())
+}
+
+// Keep the revert records of a deleted revision, without the reference to it
+pub fn detach_lyrics_tx(lyrics_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE
+      lyrics_reverts
+    SET
+      reverted_lyrics_id = NULLIF(reverted_lyrics_id, ?1),
+      restored_lyrics_id = NULLIF(restored_lyrics_id, ?1),
+      lyrics_id = NULLIF(lyrics_id, ?1)
+    WHERE
+      ?1 IN (reverted_lyrics_id, restored_lyrics_id, lyrics_id)
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute([lyrics_id])?;
+  Ok(())
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use rusqlite::Transaction;
+use indoc::indoc;
+use chrono::prelude::*;
+
+pub fn add_one_tx(lyrics_id: i64, value: i64, conn: &mut Transaction) -> Result<i64> {
+  let now = Utc::now();
+  let query = indoc! {"
+    INSERT INTO lyrics_votes (
+      lyrics_id,
+      value,
+      created_at
+    )
+    VALUES (?, ?, ?)
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row_id = statement.insert((lyrics_id, value, now))?;
+  Ok(row_id)
+}
+
+pub fn delete_by_lyrics_id_tx(lyrics_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    DELETE FROM lyrics_votes WHERE lyrics_id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute([lyrics_id])?;
+  Ok(())
+}
@@ -2,6 +2,7 @@ use anyhow::Result;
 use rusqlite::{Connection, OptionalExtension};
 use indoc::indoc;
 use chrono::prelude::*;
+use crate::entities::missing_track::{MissingTrack, MissingTrackCounts, QueuedMissingTrack};
 
 pub fn get_track_id_by_metadata(
   track_name_lower: &str,
@@ -27,13 +28,13 @@ pub fn get_track_id_by_metadata(
   let mut statement = conn.prepare(query)?;
   let row = statement.query_row(
     (track_name_lower, artist_name_lower, album_name_lower, duration - 2.0, duration + 2.0),
-    |row| {
-      Ok(row.get("id")?)
-    }
+    |row| row.get("id")
   ).optional()?;
   Ok(row)
 }
 
+// Queue a missing track, unless the same track is already queued
+#[allow(clippy::too_many_arguments)]
 pub fn add_one(
   track_name: &str,
   artist_name: &str,
@@ -43,7 +44,7 @@ pub fn add_one(
   album_name_lower: &str,
   duration: f64,
   conn: &mut Connection,
-) -> Result<i64> {
+) -> Result<()> {
   let now = Utc::now();
   let query = indoc! {"
     INSERT INTO missing_tracks (
@@ -58,9 +59,10 @@ pub fn add_one(
       updated_at
     )
     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT DO NOTHING
   "};
   let mut statement = conn.prepare(query)?;
-  let row_id = statement.insert(
+  statement.execute(
     (
       track_name,
       track_name_lower,
@@ -73,17 +75,193 @@ pub fn add_one(
       now,
     )
   )?;
-  Ok(row_id)
+  Ok(())
 }
 
-pub fn clean_old_missing_tracks(conn: &mut Connection) -> Result<()> {
-  // Delete all missing tracks older than 14 days
+// Claim the oldest available missing track. Claims older than the lease are considered abandoned,
+// e.g. by a worker that was stopped, and can be claimed again. Tracks that used up their attempts are skipped.
+pub fn claim_next(worker_id: &str, lease: chrono::Duration, max_attempts: i64, conn: &mut Connection) -> Result<Option<QueuedMissingTrack>> {
+  let now = Utc::now();
   let query = indoc! {"
-    DELETE FROM missing_tracks
-    WHERE created_at < DATETIME('now', '-14 day')
-    LIMIT 10000
+    UPDATE missing_tracks
+    SET claimed_at = ?1, claimed_by = ?2, attempts = attempts + 1, updated_at = ?1
+    WHERE id = (
+      SELECT
+        id
+      FROM
+        missing_tracks
+      WHERE
+        (claimed_at IS NULL OR claimed_at < ?3)
+        AND (available_at IS NULL OR available_at <= ?1)
+        AND attempts < ?4
+      ORDER BY
+        id
+      LIMIT 1
+    )
+    RETURNING id, name, artist_name, album_name, duration, attempts
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row(
+    (now, worker_id, now - lease, max_attempts),
+    |row| {
+      Ok(QueuedMissingTrack {
+        id: row.get("id")?,
+        attempts: row.get("attempts")?,
+        track: MissingTrack {
+          name: row.get("name")?,
+          artist_name: row.get("artist_name")?,
+          album_name: row.get("album_name")?,
+          duration: row.get("duration")?,
+        },
+      })
+    }
+  ).optional()?;
+  Ok(row)
+}
+
+// Done with the missing track, whether lyrics were found or not
+pub fn delete_one(id: i64, conn: &mut Connection) -> Result<()> {
+  let query = indoc! {"
+    DELETE FROM missing_tracks WHERE id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute([id])?;
+  Ok(())
+}
+
+// Give the missing track back to the queue, to be retried once available_at has passed
+pub fn release(id: i64, available_at: DateTime<Utc>, conn: &mut Connection) -> Result<()> {
+  let query = indoc! {"
+    UPDATE missing_tracks
+    SET claimed_at = NULL, claimed_by = NULL, available_at = ?, updated_at = ?
+    WHERE id = ?
   "};
   let mut statement = conn.prepare(query)?;
-  statement.execute(())?;
+  statement.execute((available_at, Utc::now(), id))?;
   Ok(())
 }
+
+pub fn get_counts(lease: chrono::Duration, max_attempts: i64, conn: &mut Connection) -> Result<MissingTrackCounts> {
+  let query = indoc! {"
+    SELECT
+      COUNT(*) FILTER (WHERE attempts < ?2 AND (claimed_at IS NULL OR claimed_at < ?1)) AS pending_count,
+      COUNT(*) FILTER (WHERE claimed_at >= ?1) AS claimed_count,
+      COUNT(*) FILTER (WHERE attempts >= ?2 AND (claimed_at IS NULL OR claimed_at < ?1)) AS failed_count
+    FROM
+      missing_tracks
+  "};
+  let mut statement = conn.prepare(query)?;
+  let counts = statement.query_row(
+    (Utc::now() - lease, max_attempts),
+    |row| {
+      Ok(MissingTrackCounts {
+        pending_count: row.get("pending_count")?,
+        claimed_count: row.get("claimed_count")?,
+        failed_count: row.get("failed_count")?,
+      })
+    }
+  )?;
+  Ok(counts)
+}
+
+// Failed missing tracks are kept for a while, so that they are not queued again right away.
+// Tracks that still have attempts left are never deleted.
+pub fn clean_old_missing_tracks(max_attempts: i64, conn: &mut Connection) -> Result<usize> {
+  // Delete in batches so the database is not locked for long
+  let query = indoc! {"
+    DELETE FROM missing_tracks
+    WHERE id IN (
+      SELECT
+        id
+      FROM
+        missing_tracks
+      WHERE
+        attempts >= ?
+        AND updated_at < ?
+      LIMIT 10000
+    )
+  "};
+  let mut statement = conn.prepare(query)?;
+  let deleted_count = statement.execute((max_attempts, Utc::now() - chrono::Duration::days(14)))?;
+  Ok(deleted_count)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::db::test_connection;
+
+  const LEASE_MINUTES: i64 = 10;
+  const MAX_ATTEMPTS: i64 = 2;
+
+  fn queue_track(track_name: &str, conn: &mut Connection) {
+    add_one(track_name, "Artist", "Album", track_name, "artist", "album", 200.0, conn).unwrap();
+  }
+
+  fn claim(worker_id: &str, conn: &mut Connection) -> Option<QueuedMissingTrack> {
+    claim_next(worker_id, chrono::Duration::minutes(LEASE_MINUTES), MAX_ATTEMPTS, conn).unwrap()
+  }
+
+  fn age_all(days: i64, conn: &mut Connection) {
+    let long_ago = Utc::now() - chrono::Duration::days(days);
+    conn.execute("UPDATE missing_tracks SET created_at = ?1, updated_at = ?1", [long_ago]).unwrap();
+  }
+
+  #[test]
+  fn claimed_track_is_not_claimed_twice() {
+    let mut conn = test_connection();
+    queue_track("one", &mut conn);
+
+    let queued_track = claim("a", &mut conn).unwrap();
+    assert_eq!(queued_track.track.name, "one");
+    assert_eq!(queued_track.attempts, 1);
+    assert!(claim("b", &mut conn).is_none());
+
+    delete_one(queued_track.id, &mut conn).unwrap();
+    assert!(claim("b", &mut conn).is_none());
+  }
+
+  #[test]
+  fn abandoned_claim_is_taken_over() {
+    let mut conn = test_connection();
+    queue_track("one", &mut conn);
+    claim("a", &mut conn).unwrap();
+
+    let long_ago = Utc::now() - chrono::Duration::minutes(LEASE_MINUTES + 1);
+    conn.execute("UPDATE missing_tracks SET claimed_at = ?", [long_ago]).unwrap();
+
+    assert_eq!(claim("b", &mut conn).unwrap().attempts, 2);
+  }
+
+  #[test]
+  fn released_track_waits_until_available() {
+    let mut conn = test_connection();
+    queue_track("one", &mut conn);
+    let queued_track = claim("a", &mut conn).unwrap();
+
+    release(queued_track.id, Utc::now() + chrono::Duration::minutes(1), &mut conn).unwrap();
+    assert!(claim("a", &mut conn).is_none());
+
+    release(queued_track.id, Utc::now(), &mut conn).unwrap();
+    assert!(claim("a", &mut conn).is_some());
+  }
+
+  #[test]
+  fn pending_track_outlives_the_cleanup() {
+    let mut conn = test_connection();
+    queue_track("failed", &mut conn);
+    for _ in 0..MAX_ATTEMPTS {
+      let queued_track = claim("a", &mut conn).unwrap();
+      release(queued_track.id, Utc::now(), &mut conn).unwrap();
+    }
+    queue_track("pending", &mut conn);
+    age_all(30, &mut conn);
+
+    let counts = get_counts(chrono::Duration::minutes(LEASE_MINUTES), MAX_ATTEMPTS, &mut conn).unwrap();
+    assert_eq!((counts.pending_count, counts.failed_count), (1, 1));
+
+    assert_eq!(clean_old_missing_tracks(MAX_ATTEMPTS, &mut conn).unwrap(), 1);
+    assert_eq!(claim("a", &mut conn).unwrap().track.name, "pending");
+    assert!(claim("a", &mut conn).is_none());
+  }
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension, Row, Transaction};
+use indoc::indoc;
+use chrono::prelude::*;
+use crate::entities::takedown::Takedown;
+
+fn row_to_takedown(row: &Row) -> rusqlite::Result<Takedown> {
+  Ok(Takedown {
+    id: row.get("id")?,
+    track_id: row.get("track_id")?,
+    lyrics_id: row.get("lyrics_id")?,
+    reason: row.get("reason")?,
+    actor: row.get("actor")?,
+    created_at: row.get("created_at")?,
+    lifted_at: row.get("lifted_at")?,
+    lifted_by: row.get("lifted_by")?,
+  })
+}
+
+pub fn add_one_tx(track_id: i64, lyrics_id: Option<i64>, reason: &str, actor: &str, conn: &mut Transaction) -> Result<i64> {
+  let now = Utc::now();
+  let query = indoc! {"
+    INSERT INTO takedowns (
+      track_id,
+      lyrics_id,
+      reason,
+      actor,
+      created_at
+    )
+    VALUES (?, ?, ?, ?, ?)
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row_id = statement.insert((track_id, lyrics_id, reason, actor, now))?;
+  Ok(row_id)
+}
+
+pub fn get_takedown_tx(takedown_id: i64, conn: &mut Transaction) -> Result<Option<Takedown>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      takedowns
+    WHERE
+      takedowns.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row([takedown_id], row_to_takedown).optional()?;
+  Ok(row)
+}
+
+// Takedowns in effect, newest first
+pub fn get_active_takedowns(limit: i64, offset: i64, conn: &mut Connection) -> Result<Vec<Takedown>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      takedowns
+    WHERE
+      takedowns.lifted_at IS NULL
+    ORDER BY
+      takedowns.id DESC
+    LIMIT ? OFFSET ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let rows = statement.query_map((limit, offset), row_to_takedown)?;
+  Ok(rows.collect::<rusqlite::Result<Vec<Takedown>>>()?)
+}
+
+pub fn lift_tx(takedown_id: i64, actor: &str, conn: &mut Transaction) -> Result<()> {
+  let now = Utc::now();
+  let query = indoc! {"
+    UPDATE takedowns SET lifted_at = ?, lifted_by = ? WHERE id = ? AND lifted_at IS NULL
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((now, actor, takedown_id))?;
+  Ok(())
+}
+
+pub fn move_to_track_tx(from_track_id: i64, to_track_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE takedowns SET track_id = ? WHERE track_id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((to_track_id, from_track_id))?;
+  Ok(())
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
 use rusqlite::{Connection, OptionalExtension, Transaction};
 use indoc::indoc;
+use serde_json::json;
 use crate::{
-  entities::{lyrics::SimpleLyrics, track::SimpleTrack},
+  repositories::audit_log_repository,
+  entities::{audit_log::{AuditAction, SYSTEM_ACTOR}, flag::{AutoHidePolicy, FlagReason}, lyrics::SimpleLyrics, track::{SimpleTrack, Track}},
   utils::prepare_input,
 };
 use chrono::prelude::*;
@@ -17,9 +19,11 @@ pub fn get_track_by_id(track_id: i64, conn: &mut Connection) -> Result<Option<Si
       tracks.artist_name,
       tracks.duration,
       tracks.last_lyrics_id,
+      tracks.taken_down,
       lyrics.instrumental,
       lyrics.plain_lyrics,
-      lyrics.synced_lyrics
+      lyrics.synced_lyrics,
+      lyrics.enhanced_lyrics
     FROM
       tracks
       LEFT JOIN lyrics ON tracks.last_lyrics_id = lyrics.id
@@ -30,14 +34,12 @@ pub fn get_track_by_id(track_id: i64, conn: &mut Connection) -> Result<Option<Si
   let row = statement.query_row(
     [track_id],
     |row| {
-      let instrumental = match row.get("instrumental")? {
-        Some(value) => value,
-        None => false
-      };
+      let instrumental = row.get::<_, Option<bool>>("instrumental")?.unwrap_or_default();
 
       let last_lyrics = SimpleLyrics {
         plain_lyrics: row.get("plain_lyrics")?,
         synced_lyrics: row.get("synced_lyrics")?,
+        enhanced_lyrics: row.get("enhanced_lyrics")?,
         instrumental,
       };
 
@@ -47,6 +49,7 @@ pub fn get_track_by_id(track_id: i64, conn: &mut Connection) -> Result<Option<Si
         artist_name: row.get("artist_name")?,
         album_name: row.get("album_name")?,
         duration: row.get("duration")?,
+        taken_down: row.get("taken_down")?,
         last_lyrics: Some(last_lyrics),
       })
     }
@@ -54,6 +57,82 @@ pub fn get_track_by_id(track_id: i64, conn: &mut Connection) -> Result<Option<Si
   Ok(row)
 }
 
+// Track row without its lyrics
+pub fn get_track_tx(track_id: i64, conn: &mut Transaction) -> Result<Option<Track>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      tracks
+    WHERE
+      tracks.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row(
+    [track_id],
+    |row| {
+      Ok(Track {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        artist_name: row.get("artist_name")?,
+        album_name: row.get("album_name")?,
+        duration: row.get("duration")?,
+        last_lyrics_id: row.get("last_lyrics_id")?,
+        taken_down: row.get("taken_down")?,
+        last_lyrics: None,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+      })
+    }
+  ).optional()?;
+  Ok(row)
+}
+
+// Track row without its lyrics
+pub fn get_track(track_id: i64, conn: &mut Connection) -> Result<Option<Track>> {
+  let query = indoc! {"
+    SELECT
+      *
+    FROM
+      tracks
+    WHERE
+      tracks.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row(
+    [track_id],
+    |row| {
+      Ok(Track {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        artist_name: row.get("artist_name")?,
+        album_name: row.get("album_name")?,
+        duration: row.get("duration")?,
+        last_lyrics_id: row.get("last_lyrics_id")?,
+        taken_down: row.get("taken_down")?,
+        last_lyrics: None,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+      })
+    }
+  ).optional()?;
+  Ok(row)
+}
+
+pub fn get_last_lyrics_id_tx(track_id: i64, conn: &mut Transaction) -> Result<Option<Option<i64>>> {
+  let query = indoc! {"
+    SELECT
+      tracks.last_lyrics_id
+    FROM
+      tracks
+    WHERE
+      tracks.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let row = statement.query_row([track_id], |row| row.get("last_lyrics_id")).optional()?;
+  Ok(row)
+}
+
 pub fn get_track_id_by_metadata(track_name: &str, artist_name: &str, album_name: &str, duration: f64, conn: &mut Connection) -> Result<Option<i64>> {
   let track_name_lower = prepare_input(track_name);
   let artist_name_lower = prepare_input(artist_name);
@@ -76,9 +155,7 @@ pub fn get_track_id_by_metadata(track_name: &str, artist_name: &str, album_name:
   let mut statement = conn.prepare(query)?;
   let row = statement.query_row(
     (track_name_lower, artist_name_lower, album_name_lower, duration - 2.0, duration + 2.0),
-    |row| {
-      Ok(row.get("id")?)
-    }
+    |row| row.get("id")
   ).optional()?;
   Ok(row)
 }
@@ -105,9 +182,7 @@ pub fn get_track_id_by_metadata_tx(track_name: &str, artist_name: &str, album_na
   let mut statement = conn.prepare(query)?;
   let row = statement.query_row(
     (track_name_lower, artist_name_lower, album_name_lower, duration - 2.0, duration + 2.0),
-    |row| {
-      Ok(row.get("id")?)
-    }
+    |row| row.get("id")
   ).optional()?;
   Ok(row)
 }
@@ -128,9 +203,11 @@ pub fn get_track_by_metadata(
       tracks.album_name,
       tracks.duration,
       tracks.last_lyrics_id,
+      tracks.taken_down,
       lyrics.instrumental,
       lyrics.plain_lyrics,
-      lyrics.synced_lyrics
+      lyrics.synced_lyrics,
+      lyrics.enhanced_lyrics
     FROM
       tracks
       LEFT JOIN lyrics ON tracks.last_lyrics_id = lyrics.id
@@ -179,6 +256,7 @@ pub fn get_track_by_metadata(
       let last_lyrics = SimpleLyrics {
         plain_lyrics: row.get("plain_lyrics")?,
         synced_lyrics: row.get("synced_lyrics")?,
+        enhanced_lyrics: row.get("enhanced_lyrics")?,
         instrumental,
       };
 
@@ -188,6 +266,7 @@ pub fn get_track_by_metadata(
         artist_name: row.get("artist_name")?,
         album_name: row.get("album_name")?,
         duration: row.get("duration")?,
+        taken_down: row.get("taken_down")?,
         last_lyrics: Some(last_lyrics),
       })
     }
@@ -232,24 +311,28 @@ pub fn get_tracks_by_keyword(
       tracks.artist_name,
       tracks.album_name,
       tracks.duration,
+      tracks.taken_down,
       lyrics.instrumental,
       lyrics.plain_lyrics,
-      lyrics.synced_lyrics
+      lyrics.synced_lyrics,
+      lyrics.enhanced_lyrics
     FROM
       ({subquery}) AS search_results
       LEFT JOIN tracks ON search_results.rowid = tracks.id
       LEFT JOIN lyrics ON tracks.last_lyrics_id = lyrics.id
+    WHERE
+      NOT tracks.taken_down
     ",
     subquery = subquery
   );
 
   let mut statement = conn.prepare(&query)?;
   let fts_query = match q {
-    Some(q) => prepare_input(&q),
+    Some(q) => prepare_input(q),
     None => {
       match track_name {
         Some(track_name) => {
-          let mut result = format!("(name_lower : \"{}\")", track_name).to_owned();
+          let mut result = format!("(name_lower : \"{}\")", track_name);
           if let Some(artist_name) = artist_name {
             result.push_str(format!("AND (artist_name_lower : {})", artist_name).as_ref());
           }
@@ -270,14 +353,12 @@ pub fn get_tracks_by_keyword(
   let mut tracks = Vec::new();
 
   while let Some(row) = rows.next()? {
-    let instrumental = match row.get("instrumental")? {
-      Some(value) => value,
-      None => false
-    };
+    let instrumental = row.get::<_, Option<bool>>("instrumental")?.unwrap_or_default();
 
     let last_lyrics = SimpleLyrics {
       plain_lyrics: row.get("plain_lyrics")?,
       synced_lyrics: row.get("synced_lyrics")?,
+      enhanced_lyrics: row.get("enhanced_lyrics")?,
       instrumental,
     };
 
@@ -287,6 +368,7 @@ pub fn get_tracks_by_keyword(
       artist_name: row.get("artist_name")?,
       album_name: row.get("album_name")?,
       duration: row.get("duration")?,
+      taken_down: row.get("taken_down")?,
       last_lyrics: Some(last_lyrics),
     };
 
@@ -382,14 +464,248 @@ pub fn add_one_tx(
   Ok(row_id)
 }
 
-pub fn flag_track_last_lyrics(track_id: i64, content: &str, conn: &mut Connection) -> Result<()> {
+// Flag the revision currently served for the track. Returns true if the flag made the
+// revision reach the auto-hide threshold, in which case it has been hidden, or None if the
+// track does not exist or has no lyrics to flag.
+pub fn flag_track_last_lyrics(
+  track_id: i64,
+  reason: FlagReason,
+  details: Option<&str>,
+  suggested_offset: Option<i64>,
+  token_prefix: &str,
+  auto_hide_policy: Option<AutoHidePolicy>,
+  conn: &mut Connection,
+) -> Result<Option<bool>> {
   let now = Utc::now();
+  let mut tx = conn.transaction()?;
+
+  let Some(lyrics_id) = get_last_lyrics_id_tx(track_id, &mut tx)?.flatten() else {
+    return Ok(None);
+  };
 
   let query = indoc! {"
-    INSERT INTO flags (lyrics_id, content, created_at)
-    SELECT last_lyrics_id, ?, ? FROM tracks WHERE id = ?
+    INSERT INTO flags (lyrics_id, reason, details, suggested_offset, token_prefix, created_at)
+    VALUES (?, ?, ?, ?, ?, ?)
+  "};
+  tx.execute(query, (lyrics_id, reason.as_str(), details, suggested_offset, token_prefix, now))?;
+
+  let is_auto_hidden = match auto_hide_policy {
+    Some(policy) => {
+      // Publish tokens are single-use, so every flag comes from its own solved challenge
+      // and counts as one reporter
+      let query = indoc! {"
+        SELECT
+          COUNT(*)
+        FROM
+          flags
+        WHERE
+          flags.lyrics_id = ?
+          AND flags.status = 'open'
+          AND flags.created_at >= ?
+      "};
+      let flags_count: i64 = tx.query_row(query, (lyrics_id, now - policy.window), |row| row.get(0))?;
+
+      if flags_count >= policy.threshold {
+        let query = indoc! {"
+          UPDATE lyrics SET hidden = 1, auto_hidden = 1, updated_at = ? WHERE id = ? AND NOT hidden
+        "};
+        let is_hidden = tx.execute(query, (now, lyrics_id))? > 0;
+
+        if is_hidden {
+          audit_log_repository::add_one_tx(
+            SYSTEM_ACTOR,
+            AuditAction::AutoHideLyrics,
+            Some(track_id),
+            Some(lyrics_id),
+            None,
+            Some(&json!({ "flagsCount": flags_count, "threshold": policy.threshold })),
+            &mut tx,
+          )?;
+        }
+
+        is_hidden
+      } else {
+        false
+      }
+    },
+    None => false,
+  };
+
+  tx.commit()?;
+
+  Ok(Some(is_auto_hidden))
+}
+
+// Point the track at its best visible revision, the same way the lyrics triggers do
+pub fn refresh_last_lyrics_id_tx(track_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE tracks SET last_lyrics_id = (
+      SELECT id FROM lyrics WHERE lyrics.track_id = tracks.id AND NOT lyrics.hidden ORDER BY score DESC, id DESC LIMIT 1
+    ) WHERE tracks.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute([track_id])?;
+  Ok(())
+}
+
+// Update the given metadata fields. Returns false if the track does not exist.
+pub fn update_metadata_tx(
+  track_id: i64,
+  track_name: Option<&str>,
+  artist_name: Option<&str>,
+  album_name: Option<&str>,
+  duration: Option<f64>,
+  conn: &mut Transaction,
+) -> Result<bool> {
+  let track_name_lower = track_name.map(prepare_input);
+  let artist_name_lower = artist_name.map(prepare_input);
+  let album_name_lower = album_name.map(prepare_input);
+
+  let now = Utc::now();
+  let query = indoc! {"
+    UPDATE
+      tracks
+    SET
+      name = COALESCE(?, name),
+      name_lower = COALESCE(?, name_lower),
+      artist_name = COALESCE(?, artist_name),
+      artist_name_lower = COALESCE(?, artist_name_lower),
+      album_name = COALESCE(?, album_name),
+      album_name_lower = COALESCE(?, album_name_lower),
+      duration = COALESCE(?, duration),
+      updated_at = ?
+    WHERE
+      tracks.id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  let count = statement.execute(
+    (
+      track_name,
+      track_name_lower,
+      artist_name,
+      artist_name_lower,
+      album_name,
+      album_name_lower,
+      duration,
+      now,
+      track_id,
+    )
+  )?;
+  Ok(count > 0)
+}
+
+pub fn delete_one_tx(track_id: i64, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    DELETE FROM tracks WHERE id = ?
   "};
   let mut statement = conn.prepare(query)?;
-  statement.execute((content, now, track_id))?;
+  statement.execute([track_id])?;
   Ok(())
 }
+
+pub fn set_taken_down_tx(track_id: i64, taken_down: bool, conn: &mut Transaction) -> Result<()> {
+  let query = indoc! {"
+    UPDATE tracks SET taken_down = ?, updated_at = ? WHERE id = ?
+  "};
+  let mut statement = conn.prepare(query)?;
+  statement.execute((taken_down, Utc::now(), track_id))?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{db::test_connection, repositories::lyrics_repository};
+
+  const POLICY: AutoHidePolicy = AutoHidePolicy { threshold: 3, window: chrono::Duration::hours(24) };
+
+  // A track serving "current", with "previous" as the revision to fall back to
+  fn add_track(conn: &mut Connection) -> (i64, i64, i64) {
+    let mut tx = conn.transaction().unwrap();
+    let track_id = add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let publish = |plain_lyrics: &str, tx: &mut Transaction| {
+      lyrics_repository::add_one_tx(&Some(plain_lyrics.to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), tx).unwrap()
+    };
+    let previous_id = publish("previous", &mut tx);
+    let current_id = publish("current", &mut tx);
+    tx.commit().unwrap();
+    (track_id, previous_id, current_id)
+  }
+
+  fn flag(track_id: i64, policy: Option<AutoHidePolicy>, conn: &mut Connection) -> Option<bool> {
+    flag_track_last_lyrics(track_id, FlagReason::Spam, None, None, "challenge", policy, conn).unwrap()
+  }
+
+  fn served_lyrics_id(track_id: i64, conn: &mut Connection) -> Option<i64> {
+    get_track(track_id, conn).unwrap().unwrap().last_lyrics_id
+  }
+
+  fn auto_hide_count(lyrics_id: i64, conn: &mut Connection) -> i64 {
+    conn.query_row(
+      "SELECT COUNT(*) FROM audit_logs WHERE action = ? AND lyrics_id = ? AND actor = ?",
+      (AuditAction::AutoHideLyrics.as_str(), lyrics_id, SYSTEM_ACTOR),
+      |row| row.get(0),
+    ).unwrap()
+  }
+
+  #[test]
+  fn lyrics_are_hidden_at_the_flag_threshold() {
+    let mut conn = test_connection();
+    let (track_id, previous_id, current_id) = add_track(&mut conn);
+
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), Some(false));
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), Some(false));
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(current_id));
+    assert_eq!(auto_hide_count(current_id, &mut conn), 0);
+
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), Some(true));
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(previous_id));
+    assert_eq!(auto_hide_count(current_id, &mut conn), 1);
+
+    let (hidden, auto_hidden): (bool, bool) = conn.query_row(
+      "SELECT hidden, auto_hidden FROM lyrics WHERE id = ?",
+      [current_id],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert!(hidden && auto_hidden);
+  }
+
+  #[test]
+  fn flags_outside_the_window_do_not_count() {
+    let mut conn = test_connection();
+    let (track_id, _, current_id) = add_track(&mut conn);
+
+    flag(track_id, Some(POLICY), &mut conn);
+    flag(track_id, Some(POLICY), &mut conn);
+    conn.execute("UPDATE flags SET created_at = ?", [Utc::now() - chrono::Duration::hours(25)]).unwrap();
+
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), Some(false));
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), Some(false));
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(current_id));
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), Some(true));
+  }
+
+  #[test]
+  fn lyrics_are_never_hidden_without_a_policy() {
+    let mut conn = test_connection();
+    let (track_id, _, current_id) = add_track(&mut conn);
+
+    for _ in 0..5 {
+      assert_eq!(flag(track_id, None, &mut conn), Some(false));
+    }
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(current_id));
+  }
+
+  #[test]
+  fn track_without_lyrics_cannot_be_flagged() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    assert_eq!(flag(track_id, Some(POLICY), &mut conn), None);
+    assert_eq!(flag(track_id + 1, Some(POLICY), &mut conn), None);
+    let flags_count: i64 = conn.query_row("SELECT COUNT(*) FROM flags", [], |row| row.get(0)).unwrap();
+    assert_eq!(flags_count, 0);
+  }
+}
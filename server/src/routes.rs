@@ -4,3 +4,10 @@ pub mod search_lyrics;
 pub mod request_challenge;
 pub mod publish_lyrics;
 pub mod flag_lyrics;
+pub mod validate_lyrics;
+pub mod convert_lyrics;
+pub mod get_lyrics_history;
+pub mod get_lyrics_by_id;
+pub mod get_lyrics_diff;
+pub mod admin;
+pub mod vote_lyrics;
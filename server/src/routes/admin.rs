@@ -0,0 +1,160 @@
+use axum::{
+  async_trait,
+  extract::FromRequestParts,
+  http::{header, request::Parts},
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use crate::{errors::ApiError, AppState};
+
+pub mod revert_lyrics;
+pub mod list_flags;
+pub mod resolve_flags;
+pub mod update_lyrics;
+pub mod delete_lyrics;
+pub mod update_track;
+pub mod merge_tracks;
+pub mod purge_cache;
+pub mod get_queue;
+pub mod list_audit_logs;
+pub mod create_takedown;
+pub mod list_takedowns;
+pub mod lift_takedown;
+
+// A request authenticated with the admin token. The actor is who is doing the change,
+// taken from the X-Admin-Actor header, so privileged changes can be attributed.
+pub struct Admin {
+  pub actor: String,
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for Admin {
+  type Rejection = ApiError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+    let Some(ref admin_token) = state.admin_token else {
+      return Err(ApiError::UnauthorizedError);
+    };
+
+    let bearer_token = parts.headers
+      .get(header::AUTHORIZATION)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .ok_or(ApiError::UnauthorizedError)?;
+
+    // Compare digests rather than the tokens themselves to avoid leaking the token through timing
+    if Sha256::digest(bearer_token.trim()) != Sha256::digest(admin_token) {
+      return Err(ApiError::UnauthorizedError);
+    }
+
+    let actor = parts.headers
+      .get("X-Admin-Actor")
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.trim())
+      .filter(|value| !value.is_empty())
+      .ok_or_else(|| ApiError::ValidationError("X-Admin-Actor header is required".to_owned()))?;
+
+    Ok(Admin { actor: actor.to_owned() })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use axum::{
+    extract::{Path, Query, State},
+    http::{Request, StatusCode},
+    response::IntoResponse,
+    Json,
+  };
+  use serde::de::DeserializeOwned;
+  use crate::{
+    repositories::{lyrics_repository, track_repository},
+    routes::{get_lyrics_by_id, get_lyrics_by_metadata, get_lyrics_by_track_id, get_lyrics_history, search_lyrics},
+    test_state,
+  };
+
+  pub fn admin() -> Admin {
+    Admin { actor: "mod".to_owned() }
+  }
+
+  pub fn query<T: DeserializeOwned>(uri: &str) -> Query<T> {
+    Query::try_from_uri(&uri.parse().unwrap()).unwrap()
+  }
+
+  // A track with two revisions, the second one being served
+  pub fn add_track(state: &AppState) -> (i64, i64, i64) {
+    let mut conn = state.pool.get().unwrap();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let publish = |plain_lyrics: &str, tx: &mut rusqlite::Transaction| {
+      lyrics_repository::add_one_tx(&Some(plain_lyrics.to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), tx).unwrap()
+    };
+    let first_id = publish("first", &mut tx);
+    let second_id = publish("second", &mut tx);
+    tx.commit().unwrap();
+    (track_id, first_id, second_id)
+  }
+
+  pub fn served_lyrics_id(track_id: i64, state: &AppState) -> Option<i64> {
+    let mut conn = state.pool.get().unwrap();
+    track_repository::get_track(track_id, &mut conn).unwrap().unwrap().last_lyrics_id
+  }
+
+  // Statuses of getting the track by id and by metadata, its history and the lyrics revision
+  pub async fn public_statuses(track_id: i64, lyrics_id: i64, state: &Arc<AppState>) -> [StatusCode; 4] {
+    [
+      get_lyrics_by_track_id::route(Path(track_id), query("/"), State(state.clone())).await.into_response().status(),
+      get_lyrics_by_metadata::route(query("/?track_name=Song&artist_name=Artist"), State(state.clone())).await.into_response().status(),
+      get_lyrics_history::route(Path(track_id), State(state.clone())).await.into_response().status(),
+      get_lyrics_by_id::route(Path(lyrics_id), State(state.clone())).await.into_response().status(),
+    ]
+  }
+
+  pub async fn search_track_ids(state: &Arc<AppState>) -> Vec<i64> {
+    let Ok(Json(tracks)) = search_lyrics::route(query("/?q=song%20artist"), State(state.clone())).await else {
+      panic!("search failed");
+    };
+    let tracks = serde_json::to_value(tracks).unwrap();
+    tracks.as_array().unwrap().iter().map(|track| track["id"].as_i64().unwrap()).collect()
+  }
+
+  pub async fn take_down(target: serde_json::Value, state: &Arc<AppState>) -> i64 {
+    let payload = serde_json::from_value(target).unwrap();
+    let Ok((StatusCode::CREATED, Json(response))) = create_takedown::route(admin(), State(state.clone()), Json(payload)).await else {
+      panic!("takedown failed");
+    };
+    serde_json::to_value(response).unwrap()["id"].as_i64().unwrap()
+  }
+
+  async fn extract(admin_token: Option<&str>, headers: &[(&str, &str)]) -> Result<Admin, ApiError> {
+    let mut request = Request::builder();
+    for (name, value) in headers {
+      request = request.header(*name, *value);
+    }
+    let (mut parts, _) = request.body(()).unwrap().into_parts();
+    Admin::from_request_parts(&mut parts, &test_state(admin_token)).await
+  }
+
+  #[tokio::test]
+  async fn admin_needs_the_token_and_an_actor() {
+    let authorization = ("Authorization", "Bearer secret");
+    let actor = ("X-Admin-Actor", " mod ");
+
+    let Ok(admin) = extract(Some("secret"), &[authorization, actor]).await else {
+      panic!("admin was rejected");
+    };
+    assert_eq!(admin.actor, "mod");
+
+    assert!(matches!(extract(None, &[authorization, actor]).await, Err(ApiError::UnauthorizedError)));
+    assert!(matches!(extract(Some("secret"), &[actor]).await, Err(ApiError::UnauthorizedError)));
+    let wrong_token = ("Authorization", "Bearer secrets");
+    assert!(matches!(extract(Some("secret"), &[wrong_token, actor]).await, Err(ApiError::UnauthorizedError)));
+    let wrong_scheme = ("Authorization", "Basic secret");
+    assert!(matches!(extract(Some("secret"), &[wrong_scheme, actor]).await, Err(ApiError::UnauthorizedError)));
+
+    assert!(matches!(extract(Some("secret"), &[authorization]).await, Err(ApiError::ValidationError(_))));
+    let blank_actor = ("X-Admin-Actor", " ");
+    assert!(matches!(extract(Some("secret"), &[authorization, blank_actor]).await, Err(ApiError::ValidationError(_))));
+  }
+}
@@ -0,0 +1,164 @@
+use axum::{
+  extract::State,
+  http::StatusCode,
+  Json,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use validator::Validate;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::{audit_log_repository, lyrics_repository, takedown_repository, track_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+#[derive(Validate, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTakedownRequest {
+  track_id: Option<i64>,
+  lyrics_id: Option<i64>,
+  #[validate(length(min = 1, max = 1000, message = "must be between 1 and 1000 characters"))]
+  reason: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTakedownResponse {
+  id: i64,
+  track_id: i64,
+}
+
+// Take down a whole track or a single lyrics revision. Nothing is deleted, the content
+// is only withheld until the takedown is lifted.
+pub async fn route(
+  admin: Admin,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<CreateTakedownRequest>,
+) -> Result<(StatusCode, Json<CreateTakedownResponse>), ApiError> {
+  payload.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  let (id, track_id) = {
+    let mut conn = state.pool.get()?;
+    match (payload.track_id, payload.lyrics_id) {
+      (Some(track_id), None) => take_down_track(track_id, payload.reason.trim(), &admin, &mut conn)?,
+      (None, Some(lyrics_id)) => take_down_lyrics(lyrics_id, payload.reason.trim(), &admin, &mut conn)?,
+      _ => return Err(ApiError::ValidationError("exactly one of trackId and lyricsId must be provided".to_owned())),
+    }
+  };
+
+  search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+
+  tracing::info!(message = "takedown created", takedown_id = id, track_id = track_id, lyrics_id = payload.lyrics_id, actor = admin.actor);
+
+  Ok((StatusCode::CREATED, Json(CreateTakedownResponse { id, track_id })))
+}
+
+fn take_down_track(track_id: i64, reason: &str, admin: &Admin, conn: &mut Connection) -> Result<(i64, i64), ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let track = track_repository::get_track_tx(track_id, &mut tx)?
+    .ok_or(ApiError::TrackNotFoundError)?;
+  if track.taken_down {
+    return Err(ApiError::ValidationError("trackId: the track is already taken down".to_owned()));
+  }
+
+  let id = takedown_repository::add_one_tx(track_id, None, reason, &admin.actor, &mut tx)?;
+  track_repository::set_taken_down_tx(track_id, true, &mut tx)?;
+
+  let updated_track = track_repository::get_track_tx(track_id, &mut tx)?;
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    AuditAction::TakeDown,
+    Some(track_id),
+    None,
+    Some(&json!({ "track": track })),
+    Some(&json!({ "track": updated_track, "takedownId": id, "reason": reason })),
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  Ok((id, track_id))
+}
+
+fn take_down_lyrics(lyrics_id: i64, reason: &str, admin: &Admin, conn: &mut Connection) -> Result<(i64, i64), ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+  if lyrics.taken_down {
+    return Err(ApiError::ValidationError("lyricsId: the lyrics are already taken down".to_owned()));
+  }
+
+  let id = takedown_repository::add_one_tx(lyrics.track_id, Some(lyrics_id), reason, &admin.actor, &mut tx)?;
+  // Hiding the revision lets the track fall back to its next best revision
+  lyrics_repository::set_taken_down_tx(lyrics_id, true, &mut tx)?;
+
+  let updated_lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?;
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    AuditAction::TakeDown,
+    Some(lyrics.track_id),
+    Some(lyrics_id),
+    Some(&json!({ "lyrics": lyrics })),
+    Some(&json!({ "lyrics": updated_lyrics, "takedownId": id, "reason": reason })),
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  Ok((id, lyrics.track_id))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use crate::{routes::admin::tests::*, test_state};
+
+  const UNAVAILABLE: StatusCode = StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS;
+
+  #[tokio::test]
+  async fn taken_down_track_is_unavailable() {
+    let state = test_state(None);
+    let (track_id, _, lyrics_id) = add_track(&state);
+    assert_eq!(public_statuses(track_id, lyrics_id, &state).await, [StatusCode::OK; 4]);
+    assert_eq!(search_track_ids(&state).await, vec![track_id]);
+
+    take_down(json!({ "trackId": track_id, "reason": "DMCA" }), &state).await;
+
+    assert_eq!(public_statuses(track_id, lyrics_id, &state).await, [UNAVAILABLE; 4]);
+    // The cached search result is dropped along with the track
+    assert!(search_track_ids(&state).await.is_empty());
+  }
+
+  #[tokio::test]
+  async fn taken_down_lyrics_fall_back_to_the_previous_revision() {
+    let state = test_state(None);
+    let (track_id, first_id, second_id) = add_track(&state);
+
+    take_down(json!({ "lyricsId": second_id, "reason": "DMCA" }), &state).await;
+
+    let statuses = public_statuses(track_id, second_id, &state).await;
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::OK, StatusCode::OK, UNAVAILABLE]);
+    assert_eq!(served_lyrics_id(track_id, &state), Some(first_id));
+    assert_eq!(search_track_ids(&state).await, vec![track_id]);
+  }
+
+  #[tokio::test]
+  async fn takedown_needs_exactly_one_target() {
+    let state = test_state(None);
+    let (track_id, _, lyrics_id) = add_track(&state);
+
+    for target in [json!({ "reason": "DMCA" }), json!({ "trackId": track_id, "lyricsId": lyrics_id, "reason": "DMCA" })] {
+      let payload = serde_json::from_value(target).unwrap();
+      let result = route(admin(), State(state.clone()), Json(payload)).await;
+      assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+    assert_eq!(public_statuses(track_id, lyrics_id, &state).await, [StatusCode::OK; 4]);
+  }
+}
@@ -0,0 +1,109 @@
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+};
+use rusqlite::Connection;
+use std::sync::Arc;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::{audit_log_repository, flag_repository, lyrics_repository, lyrics_revert_repository, lyrics_vote_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+// Permanently delete a lyrics revision together with its votes and flags. Prefer hiding
+// revisions, which can be undone.
+pub async fn route(
+  admin: Admin,
+  Path(lyrics_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, ApiError> {
+  let track_id = {
+    let mut conn = state.pool.get()?;
+    delete_lyrics(lyrics_id, &admin, &mut conn)?
+  };
+
+  search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+
+  tracing::info!(message = "lyrics deleted", lyrics_id = lyrics_id, track_id = track_id, actor = admin.actor);
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn delete_lyrics(lyrics_id: i64, admin: &Admin, conn: &mut Connection) -> Result<i64, ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+  // The takedown record keeps referencing the revision
+  if lyrics.taken_down {
+    return Err(ApiError::ValidationError("Lyrics that are taken down cannot be deleted".to_owned()));
+  }
+
+  // Hiding first moves the track to its next best revision, so nothing references the row anymore
+  lyrics_repository::set_hidden_tx(lyrics_id, true, &mut tx)?;
+  lyrics_vote_repository::delete_by_lyrics_id_tx(lyrics_id, &mut tx)?;
+  flag_repository::delete_by_lyrics_id_tx(lyrics_id, &mut tx)?;
+  lyrics_revert_repository::detach_lyrics_tx(lyrics_id, &mut tx)?;
+  let track_id = lyrics_repository::delete_one_tx(lyrics_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    AuditAction::DeleteLyrics,
+    Some(track_id),
+    Some(lyrics_id),
+    Some(&serde_json::to_value(&lyrics)?),
+    None,
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  Ok(track_id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    entities::flag::FlagReason,
+    repositories::track_repository,
+    routes::admin::tests::*,
+    test_state,
+  };
+
+  #[tokio::test]
+  async fn deleted_lyrics_take_their_votes_and_flags_along() {
+    let state = test_state(None);
+    let (track_id, first_id, second_id) = add_track(&state);
+    {
+      let mut conn = state.pool.get().unwrap();
+      track_repository::flag_track_last_lyrics(track_id, FlagReason::Spam, None, None, "challenge", None, &mut conn).unwrap();
+      let mut tx = conn.transaction().unwrap();
+      lyrics_vote_repository::add_one_tx(second_id, 1, &mut tx).unwrap();
+      tx.commit().unwrap();
+    }
+
+    let result = route(admin(), Path(second_id), State(state.clone())).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+    assert_eq!(served_lyrics_id(track_id, &state), Some(first_id));
+
+    let mut conn = state.pool.get().unwrap();
+    assert!(lyrics_repository::get_lyrics_by_id(second_id, &mut conn).unwrap().is_none());
+    drop(conn);
+    let result = route(admin(), Path(second_id), State(state.clone())).await;
+    assert!(matches!(result, Err(ApiError::LyricsNotFoundError)));
+  }
+
+  #[tokio::test]
+  async fn taken_down_lyrics_cannot_be_deleted() {
+    let state = test_state(None);
+    let (_, _, second_id) = add_track(&state);
+    take_down(serde_json::json!({ "lyricsId": second_id, "reason": "DMCA" }), &state).await;
+
+    let result = route(admin(), Path(second_id), State(state.clone())).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+  }
+}
@@ -0,0 +1,26 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::sync::{atomic::Ordering, Arc};
+use crate::{errors::ApiError, queue, routes::admin::Admin, AppState};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueResponse {
+  pending_count: usize,
+  // Missing tracks a worker is currently looking up
+  claimed_count: usize,
+  // Missing tracks the providers kept failing on
+  failed_count: usize,
+  recent_lyrics_count: usize,
+}
+
+pub async fn route(_admin: Admin, State(state): State<Arc<AppState>>) -> Result<Json<QueueResponse>, ApiError> {
+  let counts = queue::get_counts(&state)?;
+
+  Ok(Json(QueueResponse {
+    pending_count: counts.pending_count,
+    claimed_count: counts.claimed_count,
+    failed_count: counts.failed_count,
+    recent_lyrics_count: state.recent_lyrics_count.load(Ordering::Relaxed),
+  }))
+}
@@ -0,0 +1,104 @@
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+};
+use rusqlite::Connection;
+use serde_json::json;
+use std::sync::Arc;
+use crate::{
+  entities::{audit_log::AuditAction, takedown::Takedown},
+  errors::ApiError,
+  repositories::{audit_log_repository, lyrics_repository, takedown_repository, track_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+// Lift a takedown. A lifted lyrics revision stays hidden until it is explicitly unhidden.
+pub async fn route(
+  admin: Admin,
+  Path(takedown_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, ApiError> {
+  let takedown = {
+    let mut conn = state.pool.get()?;
+    lift_takedown(takedown_id, &admin, &mut conn)?
+  };
+  let track_id = takedown.track_id;
+
+  match takedown.lyrics_id {
+    Some(_) => search_lyrics::invalidate_track(track_id, &state.search_cache)?,
+    // The track was left out of search results entirely, so any cached query may be missing it now
+    None => state.search_cache.invalidate_all(),
+  }
+
+  tracing::info!(message = "takedown lifted", takedown_id = takedown_id, track_id = track_id, actor = admin.actor);
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn lift_takedown(takedown_id: i64, admin: &Admin, conn: &mut Connection) -> Result<Takedown, ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let takedown = takedown_repository::get_takedown_tx(takedown_id, &mut tx)?
+    .filter(|takedown| takedown.lifted_at.is_none())
+    .ok_or_else(|| ApiError::ValidationError("takedownId: no active takedown with this id".to_owned()))?;
+
+  takedown_repository::lift_tx(takedown_id, &admin.actor, &mut tx)?;
+  match takedown.lyrics_id {
+    Some(lyrics_id) => lyrics_repository::set_taken_down_tx(lyrics_id, false, &mut tx)?,
+    None => track_repository::set_taken_down_tx(takedown.track_id, false, &mut tx)?,
+  }
+
+  let updated_takedown = takedown_repository::get_takedown_tx(takedown_id, &mut tx)?;
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    AuditAction::LiftTakedown,
+    Some(takedown.track_id),
+    takedown.lyrics_id,
+    Some(&json!({ "takedown": takedown })),
+    Some(&json!({ "takedown": updated_takedown })),
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  Ok(takedown)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{routes::admin::tests::*, test_state};
+
+  #[tokio::test]
+  async fn lifting_a_track_takedown_restores_it() {
+    let state = test_state(None);
+    let (track_id, _, lyrics_id) = add_track(&state);
+    let takedown_id = take_down(json!({ "trackId": track_id, "reason": "DMCA" }), &state).await;
+    assert!(search_track_ids(&state).await.is_empty());
+
+    let result = route(admin(), Path(takedown_id), State(state.clone())).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+
+    assert_eq!(public_statuses(track_id, lyrics_id, &state).await, [StatusCode::OK; 4]);
+    assert_eq!(served_lyrics_id(track_id, &state), Some(lyrics_id));
+    assert_eq!(search_track_ids(&state).await, vec![track_id]);
+
+    let result = route(admin(), Path(takedown_id), State(state.clone())).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+  }
+
+  #[tokio::test]
+  async fn lifted_lyrics_stay_hidden() {
+    let state = test_state(None);
+    let (track_id, first_id, second_id) = add_track(&state);
+    let takedown_id = take_down(json!({ "lyricsId": second_id, "reason": "DMCA" }), &state).await;
+
+    let result = route(admin(), Path(takedown_id), State(state.clone())).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+
+    let statuses = public_statuses(track_id, second_id, &state).await;
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::OK, StatusCode::OK, StatusCode::NOT_FOUND]);
+    assert_eq!(served_lyrics_id(track_id, &state), Some(first_id));
+  }
+}
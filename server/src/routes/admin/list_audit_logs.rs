@@ -0,0 +1,76 @@
+use axum::{extract::{Query, State}, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use validator::Validate;
+use crate::{
+  entities::audit_log::{AuditAction, AuditLog, AuditLogFilter},
+  errors::ApiError,
+  repositories::audit_log_repository,
+  routes::admin::Admin,
+  AppState,
+};
+
+#[derive(Validate, Deserialize)]
+pub struct QueryParams {
+  actor: Option<String>,
+  action: Option<AuditAction>,
+  track_id: Option<i64>,
+  lyrics_id: Option<i64>,
+  since: Option<DateTime<Utc>>,
+  until: Option<DateTime<Utc>>,
+  #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+  limit: Option<i64>,
+  #[validate(range(min = 0, message = "must not be negative"))]
+  offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogResponse {
+  id: i64,
+  actor: String,
+  action: String,
+  track_id: Option<i64>,
+  lyrics_id: Option<i64>,
+  before: Option<serde_json::Value>,
+  after: Option<serde_json::Value>,
+  created_at: DateTime<Utc>,
+}
+
+pub async fn route(
+  _admin: Admin,
+  Query(params): Query<QueryParams>,
+  State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AuditLogResponse>>, ApiError> {
+  params.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  let filter = AuditLogFilter {
+    actor: params.actor,
+    action: params.action,
+    track_id: params.track_id,
+    lyrics_id: params.lyrics_id,
+    since: params.since,
+    until: params.until,
+  };
+
+  let audit_logs = {
+    let mut conn = state.pool.get()?;
+    audit_log_repository::get_audit_logs(&filter, params.limit.unwrap_or(50), params.offset.unwrap_or(0), &mut conn)?
+  };
+
+  Ok(Json(audit_logs.into_iter().map(create_response).collect()))
+}
+
+fn create_response(audit_log: AuditLog) -> AuditLogResponse {
+  AuditLogResponse {
+    id: audit_log.id,
+    actor: audit_log.actor,
+    action: audit_log.action,
+    track_id: audit_log.track_id,
+    lyrics_id: audit_log.lyrics_id,
+    before: audit_log.before,
+    after: audit_log.after,
+    created_at: audit_log.created_at,
+  }
+}
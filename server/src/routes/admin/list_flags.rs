@@ -0,0 +1,108 @@
+use axum::{extract::{Query, State}, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use validator::Validate;
+use crate::{
+  entities::flag::{Flag, FlagGroup, FlagReason, FlagStatus},
+  errors::ApiError,
+  repositories::flag_repository,
+  routes::admin::Admin,
+  AppState,
+};
+
+#[derive(Validate, Deserialize)]
+pub struct QueryParams {
+  status: Option<FlagStatus>,
+  #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+  limit: Option<i64>,
+  #[validate(range(min = 0, message = "must not be negative"))]
+  offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlagGroupResponse {
+  lyrics_id: i64,
+  track_id: i64,
+  track_name: Option<String>,
+  artist_name: Option<String>,
+  album_name: Option<String>,
+  hidden: bool,
+  auto_hidden: bool,
+  flags_count: i64,
+  // Number of flags for each reason
+  reasons: BTreeMap<&'static str, usize>,
+  first_flagged_at: Option<DateTime<Utc>>,
+  last_flagged_at: Option<DateTime<Utc>>,
+  flags: Vec<FlagResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlagResponse {
+  id: i64,
+  reason: FlagReason,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  details: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  suggested_offset: Option<i64>,
+  status: FlagStatus,
+  reviewer: Option<String>,
+  resolution_notes: Option<String>,
+  created_at: Option<DateTime<Utc>>,
+  resolved_at: Option<DateTime<Utc>>,
+}
+
+// Flags grouped by the lyrics revision they were filed against, most flagged first
+pub async fn route(
+  _admin: Admin,
+  Query(params): Query<QueryParams>,
+  State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<FlagGroupResponse>>, ApiError> {
+  params.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  let status = params.status.unwrap_or(FlagStatus::Open);
+  let mut conn = state.pool.get()?;
+  let groups = flag_repository::get_flag_groups(status, params.limit.unwrap_or(20), params.offset.unwrap_or(0), &mut conn)?;
+
+  let mut response = vec![];
+  for group in groups {
+    let flags = flag_repository::get_flags_by_lyrics_id(group.lyrics_id, status, &mut conn)?;
+    response.push(create_response(group, flags));
+  }
+
+  Ok(Json(response))
+}
+
+fn create_response(group: FlagGroup, flags: Vec<Flag>) -> FlagGroupResponse {
+  let mut reasons = BTreeMap::new();
+  for flag in &flags {
+    *reasons.entry(flag.reason.as_str()).or_insert(0) += 1;
+  }
+
+  FlagGroupResponse {
+    lyrics_id: group.lyrics_id,
+    track_id: group.track_id,
+    track_name: group.track_name,
+    artist_name: group.artist_name,
+    album_name: group.album_name,
+    hidden: group.hidden,
+    auto_hidden: group.auto_hidden,
+    flags_count: group.flags_count,
+    reasons,
+    first_flagged_at: group.first_flagged_at,
+    last_flagged_at: group.last_flagged_at,
+    flags: flags.into_iter().map(|flag| FlagResponse {
+      id: flag.id,
+      reason: flag.reason,
+      details: flag.details,
+      suggested_offset: flag.suggested_offset,
+      status: flag.status,
+      reviewer: flag.reviewer,
+      resolution_notes: flag.resolution_notes,
+      created_at: flag.created_at,
+      resolved_at: flag.resolved_at,
+    }).collect(),
+  }
+}
@@ -0,0 +1,33 @@
+use axum::{extract::{Query, State}, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+use validator::Validate;
+use crate::{
+  entities::takedown::Takedown,
+  errors::ApiError,
+  repositories::takedown_repository,
+  routes::admin::Admin,
+  AppState,
+};
+
+#[derive(Validate, Deserialize)]
+pub struct QueryParams {
+  #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+  limit: Option<i64>,
+  #[validate(range(min = 0, message = "must not be negative"))]
+  offset: Option<i64>,
+}
+
+// Takedowns that are currently in effect, newest first
+pub async fn route(
+  _admin: Admin,
+  Query(params): Query<QueryParams>,
+  State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Takedown>>, ApiError> {
+  params.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  let mut conn = state.pool.get()?;
+  let takedowns = takedown_repository::get_active_takedowns(params.limit.unwrap_or(50), params.offset.unwrap_or(0), &mut conn)?;
+
+  Ok(Json(takedowns))
+}
@@ -0,0 +1,157 @@
+use axum::{
+  extract::{Path, State},
+  Json,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::{audit_log_repository, lyrics_repository, lyrics_revert_repository, takedown_repository, track_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeTracksRequest {
+  into_track_id: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeTracksResponse {
+  track_id: i64,
+  moved_lyrics_count: usize,
+}
+
+// Move all lyrics revisions of a duplicate track to another track and delete the duplicate
+pub async fn route(
+  admin: Admin,
+  Path(track_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<MergeTracksRequest>,
+) -> Result<Json<MergeTracksResponse>, ApiError> {
+  if track_id == payload.into_track_id {
+    return Err(ApiError::ValidationError("intoTrackId: must be a different track".to_owned()));
+  }
+
+  let moved_lyrics_count = {
+    let mut conn = state.pool.get()?;
+    merge_tracks(track_id, payload.into_track_id, &admin, &mut conn)?
+  };
+
+  search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+  search_lyrics::invalidate_track(payload.into_track_id, &state.search_cache)?;
+
+  tracing::info!(
+    message = "tracks merged",
+    track_id = track_id,
+    into_track_id = payload.into_track_id,
+    actor = admin.actor,
+  );
+
+  Ok(Json(MergeTracksResponse { track_id: payload.into_track_id, moved_lyrics_count }))
+}
+
+fn merge_tracks(track_id: i64, into_track_id: i64, admin: &Admin, conn: &mut Connection) -> Result<usize, ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let track = track_repository::get_track_tx(track_id, &mut tx)?
+    .ok_or(ApiError::TrackNotFoundError)?;
+  let into_track = track_repository::get_track_tx(into_track_id, &mut tx)?
+    .ok_or(ApiError::TrackNotFoundError)?;
+  // Takedowns are recorded against the track, so it has to stay around
+  if track.taken_down || into_track.taken_down {
+    return Err(ApiError::ValidationError("Tracks that are taken down cannot be merged".to_owned()));
+  }
+
+  let moved_lyrics_count = lyrics_repository::move_to_track_tx(track_id, into_track_id, &mut tx)?;
+  lyrics_revert_repository::move_to_track_tx(track_id, into_track_id, &mut tx)?;
+  takedown_repository::move_to_track_tx(track_id, into_track_id, &mut tx)?;
+  track_repository::refresh_last_lyrics_id_tx(into_track_id, &mut tx)?;
+  track_repository::delete_one_tx(track_id, &mut tx)?;
+
+  let updated_into_track = track_repository::get_track_tx(into_track_id, &mut tx)?;
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    AuditAction::MergeTracks,
+    Some(track_id),
+    None,
+    Some(&json!({ "track": track, "intoTrack": into_track })),
+    Some(&json!({ "intoTrack": updated_into_track, "movedLyricsCount": moved_lyrics_count })),
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  Ok(moved_lyrics_count)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{routes::admin::tests::*, test_state};
+
+  fn add_duplicate(state: &AppState) -> (i64, i64) {
+    let mut conn = state.pool.get().unwrap();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album (Remastered)", 200.0, &mut tx).unwrap();
+    let lyrics_id = lyrics_repository::add_one_tx(&Some("duplicate".to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), &mut tx).unwrap();
+    tx.commit().unwrap();
+    (track_id, lyrics_id)
+  }
+
+  fn request(into_track_id: i64) -> Json<MergeTracksRequest> {
+    Json(MergeTracksRequest { into_track_id })
+  }
+
+  #[tokio::test]
+  async fn lyrics_are_moved_and_the_duplicate_is_deleted() {
+    let state = test_state(None);
+    let (into_track_id, first_id, second_id) = add_track(&state);
+    let (track_id, duplicate_lyrics_id) = add_duplicate(&state);
+    let takedown_id = take_down(serde_json::json!({ "lyricsId": duplicate_lyrics_id, "reason": "DMCA" }), &state).await;
+
+    let Ok(Json(response)) = route(admin(), Path(track_id), State(state.clone()), request(into_track_id)).await else {
+      panic!("merge failed");
+    };
+    assert_eq!((response.track_id, response.moved_lyrics_count), (into_track_id, 1));
+
+    let mut conn = state.pool.get().unwrap();
+    assert!(track_repository::get_track(track_id, &mut conn).unwrap().is_none());
+    let lyrics_ids: Vec<i64> = lyrics_repository::get_lyrics_by_track_id(into_track_id, &mut conn).unwrap()
+      .iter()
+      .map(|lyrics| lyrics.id)
+      .collect();
+    assert_eq!(lyrics_ids.len(), 3);
+    assert!([first_id, second_id, duplicate_lyrics_id].iter().all(|lyrics_id| lyrics_ids.contains(lyrics_id)));
+    // The moved revision is taken down, so the track keeps serving its own
+    assert_eq!(track_repository::get_track(into_track_id, &mut conn).unwrap().unwrap().last_lyrics_id, Some(second_id));
+
+    let mut tx = conn.transaction().unwrap();
+    let takedown = takedown_repository::get_takedown_tx(takedown_id, &mut tx).unwrap().unwrap();
+    assert_eq!(takedown.track_id, into_track_id);
+  }
+
+  #[tokio::test]
+  async fn merge_needs_two_existing_tracks() {
+    let state = test_state(None);
+    let (into_track_id, _, _) = add_track(&state);
+    let (track_id, _) = add_duplicate(&state);
+
+    let result = route(admin(), Path(track_id), State(state.clone()), request(track_id)).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    let result = route(admin(), Path(track_id), State(state.clone()), request(0)).await;
+    assert!(matches!(result, Err(ApiError::TrackNotFoundError)));
+
+    take_down(serde_json::json!({ "trackId": into_track_id, "reason": "DMCA" }), &state).await;
+    let result = route(admin(), Path(track_id), State(state.clone()), request(into_track_id)).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+    let mut conn = state.pool.get().unwrap();
+    assert!(track_repository::get_track(track_id, &mut conn).unwrap().is_some());
+  }
+}
@@ -0,0 +1,86 @@
+use axum::{
+  extract::{Query, State},
+  http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::audit_log_repository,
+  routes::admin::Admin,
+  AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheName {
+  Search,
+  Get,
+  All,
+}
+
+#[derive(Deserialize)]
+pub struct QueryParams {
+  cache: Option<CacheName>,
+}
+
+// Drop cached responses. Publish challenges are never purged.
+pub async fn route(
+  admin: Admin,
+  Query(params): Query<QueryParams>,
+  State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, ApiError> {
+  let cache = params.cache.unwrap_or(CacheName::All);
+
+  if matches!(cache, CacheName::Search | CacheName::All) {
+    state.search_cache.invalidate_all();
+  }
+  if matches!(cache, CacheName::Get | CacheName::All) {
+    state.get_cache.invalidate_all();
+  }
+
+  {
+    let mut conn = state.pool.get()?;
+    let mut tx = conn.transaction()?;
+    audit_log_repository::add_one_tx(
+      &admin.actor,
+      AuditAction::PurgeCache,
+      None,
+      None,
+      None,
+      Some(&json!({ "cache": cache })),
+      &mut tx,
+    )?;
+    tx.commit()?;
+  }
+
+  tracing::info!(message = "cache purged", cache = ?cache, actor = admin.actor);
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{routes::admin::tests::*, test_state};
+
+  #[tokio::test]
+  async fn only_the_named_cache_is_purged() {
+    let state = test_state(None);
+    for cache in [&state.search_cache, &state.get_cache, &state.challenge_cache] {
+      cache.insert("key".to_owned(), "value".to_owned()).await;
+    }
+
+    let result = route(admin(), query("/?cache=search"), State(state.clone())).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+    assert!(state.search_cache.get("key").await.is_none());
+    assert!(state.get_cache.get("key").await.is_some());
+
+    let result = route(admin(), query("/"), State(state.clone())).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+    assert!(state.get_cache.get("key").await.is_none());
+    assert!(state.challenge_cache.get("key").await.is_some());
+  }
+}
@@ -0,0 +1,283 @@
+use axum::{
+  extract::{Path, State},
+  Json,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use validator::Validate;
+use crate::{
+  entities::{audit_log::AuditAction, flag::FlagStatus},
+  errors::ApiError,
+  repositories::{audit_log_repository, flag_repository, lyrics_repository},
+  routes::{admin::{revert_lyrics::revert_lyrics_tx, Admin}, search_lyrics},
+  AppState,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolveAction {
+  Hide,
+  Revert,
+}
+
+#[derive(Validate, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveFlagsRequest {
+  status: FlagStatus,
+  #[validate(length(max = 1000, message = "must not be longer than 1000 characters"))]
+  notes: Option<String>,
+  // What to do with the flagged revision when accepting its flags
+  action: Option<ResolveAction>,
+  // Revision to restore for the revert action
+  revert_to: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveFlagsResponse {
+  resolved_count: usize,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  reverted_lyrics_id: Option<i64>,
+}
+
+// Resolve all open flags of a lyrics revision
+pub async fn route(
+  admin: Admin,
+  Path(lyrics_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<ResolveFlagsRequest>,
+) -> Result<Json<ResolveFlagsResponse>, ApiError> {
+  payload.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  match (payload.status, payload.action) {
+    (FlagStatus::Open, _) => {
+      return Err(ApiError::ValidationError("status: must be accepted or rejected".to_owned()));
+    },
+    (FlagStatus::Rejected, Some(_)) => {
+      return Err(ApiError::ValidationError("action: only allowed when accepting flags".to_owned()));
+    },
+    (_, Some(ResolveAction::Revert)) if payload.revert_to.is_none() => {
+      return Err(ApiError::ValidationError("revertTo: required for the revert action".to_owned()));
+    },
+    _ => {},
+  }
+
+  let (track_id, is_served_lyrics_changed, response) = {
+    let mut conn = state.pool.get()?;
+    resolve_flags(lyrics_id, &payload, &admin, &mut conn)?
+  };
+
+  if is_served_lyrics_changed {
+    search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+  }
+
+  tracing::info!(
+    message = "flags resolved",
+    lyrics_id = lyrics_id,
+    status = payload.status.as_str(),
+    resolved_count = response.resolved_count,
+    actor = admin.actor,
+  );
+
+  Ok(Json(response))
+}
+
+fn resolve_flags(
+  lyrics_id: i64,
+  payload: &ResolveFlagsRequest,
+  admin: &Admin,
+  conn: &mut Connection,
+) -> Result<(i64, bool, ResolveFlagsResponse), ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+  let open_flags = flag_repository::get_flags_by_lyrics_id_tx(lyrics_id, FlagStatus::Open, &mut tx)?;
+
+  let notes = payload.notes.as_deref().map(|notes| notes.trim()).filter(|notes| !notes.is_empty());
+  let resolved_count = flag_repository::resolve_open_flags_by_lyrics_id_tx(
+    lyrics_id,
+    payload.status,
+    &admin.actor,
+    notes,
+    &mut tx,
+  )?;
+
+  if resolved_count == 0 {
+    return Err(ApiError::ValidationError("The specified lyrics do not have open flags".to_owned()));
+  }
+
+  let reverted_lyrics_id = match (payload.action, payload.revert_to) {
+    (Some(ResolveAction::Hide), _) => {
+      lyrics_repository::set_hidden_tx(lyrics_id, true, &mut tx)?;
+      None
+    },
+    (Some(ResolveAction::Revert), Some(revert_to)) => Some(revert_lyrics_tx(
+      lyrics.track_id,
+      revert_to,
+      &admin.actor,
+      notes.unwrap_or("flags accepted"),
+      false,
+      &mut tx,
+    )?),
+    // The flags that hid the revision turned out to be unjustified
+    _ if payload.status == FlagStatus::Rejected && lyrics.auto_hidden && !lyrics.taken_down => {
+      lyrics_repository::set_hidden_tx(lyrics_id, false, &mut tx)?;
+      None
+    },
+    _ => None,
+  };
+
+  let updated_lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?;
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    AuditAction::ResolveFlags,
+    Some(lyrics.track_id),
+    Some(lyrics_id),
+    Some(&json!({ "lyrics": lyrics, "flags": open_flags })),
+    Some(&json!({
+      "lyrics": updated_lyrics,
+      "status": payload.status,
+      "notes": notes,
+      "action": payload.action,
+      "revertedLyricsId": reverted_lyrics_id,
+    })),
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  let is_served_lyrics_changed = payload.action.is_some()
+    || (payload.status == FlagStatus::Rejected && lyrics.auto_hidden);
+
+  Ok((lyrics.track_id, is_served_lyrics_changed, ResolveFlagsResponse { resolved_count, reverted_lyrics_id }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    db::test_connection,
+    entities::flag::{AutoHidePolicy, FlagReason},
+    repositories::track_repository,
+  };
+
+  // A track serving "flagged" with one open flag, and "previous" to fall back to
+  fn add_flagged_track(policy: Option<AutoHidePolicy>, conn: &mut Connection) -> (i64, i64, i64) {
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let publish = |plain_lyrics: &str, tx: &mut rusqlite::Transaction| {
+      lyrics_repository::add_one_tx(&Some(plain_lyrics.to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), tx).unwrap()
+    };
+    let previous_id = publish("previous", &mut tx);
+    let flagged_id = publish("flagged", &mut tx);
+    tx.commit().unwrap();
+
+    track_repository::flag_track_last_lyrics(track_id, FlagReason::Spam, None, None, "challenge", policy, conn).unwrap();
+    (track_id, previous_id, flagged_id)
+  }
+
+  fn request(status: FlagStatus, action: Option<ResolveAction>, revert_to: Option<i64>) -> ResolveFlagsRequest {
+    ResolveFlagsRequest { status, notes: Some(" checked ".to_owned()), action, revert_to }
+  }
+
+  fn admin() -> Admin {
+    Admin { actor: "mod".to_owned() }
+  }
+
+  fn served_lyrics_id(track_id: i64, conn: &mut Connection) -> Option<i64> {
+    track_repository::get_track(track_id, conn).unwrap().unwrap().last_lyrics_id
+  }
+
+  fn flag_statuses(lyrics_id: i64, conn: &mut Connection) -> Vec<(String, Option<String>, Option<String>)> {
+    let mut statement = conn.prepare("SELECT status, reviewer, resolution_notes FROM flags WHERE lyrics_id = ?").unwrap();
+    let rows = statement.query_map([lyrics_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap();
+    rows.collect::<rusqlite::Result<Vec<_>>>().unwrap()
+  }
+
+  #[test]
+  fn hide_action_serves_the_previous_revision() {
+    let mut conn = test_connection();
+    let (track_id, previous_id, flagged_id) = add_flagged_track(None, &mut conn);
+
+    let result = resolve_flags(flagged_id, &request(FlagStatus::Accepted, Some(ResolveAction::Hide), None), &admin(), &mut conn);
+    let Ok((_, is_served_lyrics_changed, response)) = result else {
+      panic!("resolving failed");
+    };
+    assert!(is_served_lyrics_changed);
+    assert_eq!(response.resolved_count, 1);
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(previous_id));
+    assert_eq!(flag_statuses(flagged_id, &mut conn), [("accepted".to_owned(), Some("mod".to_owned()), Some("checked".to_owned()))]);
+
+    let audit_logs_count: i64 = conn.query_row(
+      "SELECT COUNT(*) FROM audit_logs WHERE action = ? AND lyrics_id = ?",
+      (AuditAction::ResolveFlags.as_str(), flagged_id),
+      |row| row.get(0),
+    ).unwrap();
+    assert_eq!(audit_logs_count, 1);
+  }
+
+  #[test]
+  fn revert_action_restores_the_given_revision() {
+    let mut conn = test_connection();
+    let (track_id, previous_id, flagged_id) = add_flagged_track(None, &mut conn);
+
+    let result = resolve_flags(flagged_id, &request(FlagStatus::Accepted, Some(ResolveAction::Revert), Some(previous_id)), &admin(), &mut conn);
+    let Ok((_, _, response)) = result else {
+      panic!("resolving failed");
+    };
+    let reverted_lyrics_id = response.reverted_lyrics_id.unwrap();
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(reverted_lyrics_id));
+    let reverted_lyrics = lyrics_repository::get_lyrics_by_id(reverted_lyrics_id, &mut conn).unwrap().unwrap();
+    assert_eq!(reverted_lyrics.plain_lyrics.as_deref(), Some("previous"));
+  }
+
+  #[test]
+  fn rejecting_unhides_an_auto_hidden_revision() {
+    let mut conn = test_connection();
+    let policy = AutoHidePolicy { threshold: 1, window: chrono::Duration::hours(24) };
+    let (track_id, previous_id, flagged_id) = add_flagged_track(Some(policy), &mut conn);
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(previous_id));
+
+    let result = resolve_flags(flagged_id, &request(FlagStatus::Rejected, None, None), &admin(), &mut conn);
+    let Ok((_, is_served_lyrics_changed, _)) = result else {
+      panic!("resolving failed");
+    };
+    assert!(is_served_lyrics_changed);
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(flagged_id));
+    let lyrics = lyrics_repository::get_lyrics_by_id(flagged_id, &mut conn).unwrap().unwrap();
+    assert!(!lyrics.hidden && !lyrics.auto_hidden);
+    assert_eq!(flag_statuses(flagged_id, &mut conn)[0].0, "rejected");
+  }
+
+  #[test]
+  fn rejecting_keeps_a_revision_hidden_by_a_moderator() {
+    let mut conn = test_connection();
+    let (track_id, previous_id, flagged_id) = add_flagged_track(None, &mut conn);
+    let mut tx = conn.transaction().unwrap();
+    lyrics_repository::set_hidden_tx(flagged_id, true, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    let result = resolve_flags(flagged_id, &request(FlagStatus::Rejected, None, None), &admin(), &mut conn);
+    let Ok((_, is_served_lyrics_changed, _)) = result else {
+      panic!("resolving failed");
+    };
+    assert!(!is_served_lyrics_changed);
+    assert_eq!(served_lyrics_id(track_id, &mut conn), Some(previous_id));
+  }
+
+  #[test]
+  fn resolving_needs_open_flags() {
+    let mut conn = test_connection();
+    let (_, previous_id, flagged_id) = add_flagged_track(None, &mut conn);
+
+    let result = resolve_flags(previous_id, &request(FlagStatus::Rejected, None, None), &admin(), &mut conn);
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+    assert!(resolve_flags(flagged_id, &request(FlagStatus::Rejected, None, None), &admin(), &mut conn).is_ok());
+    let result = resolve_flags(flagged_id, &request(FlagStatus::Rejected, None, None), &admin(), &mut conn);
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+  }
+}
@@ -0,0 +1,256 @@
+use anyhow::Result;
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use rusqlite::Transaction;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use validator::Validate;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::{audit_log_repository, lyrics_repository, lyrics_revert_repository, track_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+#[derive(Validate, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertRequest {
+  lyrics_id: i64,
+  #[validate(length(min = 1, max = 1000, message = "must be between 1 and 1000 characters"))]
+  reason: String,
+  // Hidden revisions were hidden by a moderator or by flags, restoring one has to be explicit
+  #[serde(default)]
+  allow_hidden: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertResponse {
+  lyrics_id: i64,
+}
+
+// Restore an earlier revision of the track's lyrics. The revision is copied as a new
+// lyrics row with source "revert" so that the history stays append-only.
+pub async fn route(
+  admin: Admin,
+  Path(track_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<RevertRequest>,
+) -> Result<(StatusCode, Json<RevertResponse>), ApiError> {
+  payload.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  let lyrics_id = {
+    let mut conn = state.pool.get()?;
+    let mut tx = conn.transaction()?;
+    let lyrics_id = revert_lyrics_tx(track_id, payload.lyrics_id, &admin.actor, payload.reason.trim(), payload.allow_hidden, &mut tx)?;
+    tx.commit()?;
+    lyrics_id
+  };
+
+  search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+
+  tracing::info!(
+    message = "lyrics reverted",
+    track_id = track_id,
+    restored_lyrics_id = payload.lyrics_id,
+    lyrics_id = lyrics_id,
+    actor = admin.actor,
+  );
+
+  Ok((StatusCode::CREATED, Json(RevertResponse { lyrics_id })))
+}
+
+pub fn revert_lyrics_tx(
+  track_id: i64,
+  restored_lyrics_id: i64,
+  actor: &str,
+  reason: &str,
+  allow_hidden: bool,
+  tx: &mut Transaction,
+) -> Result<i64, ApiError> {
+  let track = track_repository::get_track_tx(track_id, tx)?
+    .ok_or(ApiError::TrackNotFoundError)?;
+  let current_lyrics_id = track.last_lyrics_id;
+
+  let restored_lyrics = lyrics_repository::get_lyrics_by_id_tx(restored_lyrics_id, tx)?
+    .filter(|lyrics| lyrics.track_id == track_id)
+    .ok_or(ApiError::LyricsNotFoundError)?;
+
+  if restored_lyrics.taken_down {
+    return Err(ApiError::ValidationError("Lyrics that are taken down cannot be restored".to_owned()));
+  }
+  if restored_lyrics.hidden && !allow_hidden {
+    return Err(ApiError::ConflictError("The specified lyrics are hidden, set allowHidden to restore them anyway".to_owned()));
+  }
+  if current_lyrics_id == Some(restored_lyrics.id) {
+    return Err(ApiError::ValidationError("The specified lyrics are already the current revision".to_owned()));
+  }
+
+  let score = lyrics_repository::get_next_top_score_tx(track_id, tx)?;
+  let lyrics_id = lyrics_repository::add_one_tx(
+    &restored_lyrics.plain_lyrics,
+    &restored_lyrics.synced_lyrics,
+    &restored_lyrics.enhanced_lyrics,
+    track_id,
+    restored_lyrics.instrumental,
+    &Some("revert".to_owned()),
+    tx,
+  )?;
+
+  // The copy starts above every visible revision rather than with the restored revision's votes,
+  // which would put it back below the revision a moderator is reverting away from
+  lyrics_repository::set_score_tx(lyrics_id, score, tx)?;
+
+  lyrics_revert_repository::add_one_tx(
+    track_id,
+    current_lyrics_id,
+    restored_lyrics.id,
+    lyrics_id,
+    actor,
+    reason,
+    tx,
+  )?;
+
+  let updated_track = track_repository::get_track_tx(track_id, tx)?;
+  audit_log_repository::add_one_tx(
+    actor,
+    AuditAction::RevertLyrics,
+    Some(track_id),
+    Some(lyrics_id),
+    Some(&json!({ "track": track })),
+    Some(&json!({
+      "track": updated_track,
+      "restoredLyricsId": restored_lyrics.id,
+      "restoredHidden": restored_lyrics.hidden,
+    })),
+    tx,
+  )?;
+
+  Ok(lyrics_id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    db::test_connection,
+    repositories::track_repository::get_track_by_id,
+    routes::publish_lyrics::{prepare_lyrics, publish_lyrics, PublishRequest},
+  };
+
+  fn publish(track_id: i64, plain_lyrics: &str, tx: &mut Transaction) -> i64 {
+    lyrics_repository::add_one_tx(&Some(plain_lyrics.to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), tx).unwrap()
+  }
+
+  fn served_lyrics(track_id: i64, conn: &mut rusqlite::Connection) -> Option<String> {
+    get_track_by_id(track_id, conn).unwrap().unwrap().last_lyrics.unwrap().plain_lyrics
+  }
+
+  #[test]
+  fn revert_is_served_over_an_upvoted_revision() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let original_id = publish(track_id, "original", &mut tx);
+    let vandalized_id = publish(track_id, "vandalized", &mut tx);
+    lyrics_repository::add_score_tx(vandalized_id, 10, &mut tx).unwrap();
+    tx.commit().unwrap();
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("vandalized"));
+
+    let mut tx = conn.transaction().unwrap();
+    let Ok(lyrics_id) = revert_lyrics_tx(track_id, original_id, "mod", "vandalism", false, &mut tx) else {
+      panic!("revert failed");
+    };
+    tx.commit().unwrap();
+
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("original"));
+    let track = track_repository::get_track(track_id, &mut conn).unwrap().unwrap();
+    assert_eq!(track.last_lyrics_id, Some(lyrics_id));
+
+    // Votes still count against the restored copy
+    let mut tx = conn.transaction().unwrap();
+    lyrics_repository::add_score_tx(lyrics_id, -2, &mut tx).unwrap();
+    tx.commit().unwrap();
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("vandalized"));
+  }
+
+  #[test]
+  fn upvoted_revision_is_served_over_a_newer_one() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let older_id = publish(track_id, "older", &mut tx);
+    publish(track_id, "newer", &mut tx);
+    tx.commit().unwrap();
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("newer"));
+
+    let mut tx = conn.transaction().unwrap();
+    lyrics_repository::add_score_tx(older_id, 1, &mut tx).unwrap();
+    tx.commit().unwrap();
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("older"));
+  }
+
+  #[test]
+  fn publish_is_served_over_an_upvoted_revision_until_voted_down() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let upvoted_id = publish(track_id, "upvoted", &mut tx);
+    lyrics_repository::add_score_tx(upvoted_id, 3, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    let payload = PublishRequest {
+      track_name: "Song".to_owned(),
+      artist_name: "Artist".to_owned(),
+      album_name: "Album".to_owned(),
+      duration: 200.0,
+      plain_lyrics: Some("fresh".to_owned()),
+      synced_lyrics: None,
+      synced_format: None,
+    };
+    assert!(publish_lyrics(&payload, &prepare_lyrics(&payload), &mut conn).is_ok());
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("fresh"));
+
+    // The fresh revision starts one above the upvoted one, so two downvotes put it below
+    let fresh_id = track_repository::get_track(track_id, &mut conn).unwrap().unwrap().last_lyrics_id.unwrap();
+    let mut tx = conn.transaction().unwrap();
+    lyrics_repository::add_score_tx(fresh_id, -2, &mut tx).unwrap();
+    tx.commit().unwrap();
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("upvoted"));
+  }
+
+  #[test]
+  fn downvoted_revision_falls_back_to_an_earlier_one() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    publish(track_id, "good", &mut tx);
+    let bad_id = publish(track_id, "bad", &mut tx);
+    lyrics_repository::add_score_tx(bad_id, -1, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("good"));
+  }
+
+  #[test]
+  fn hidden_revision_needs_an_explicit_override() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let hidden_id = publish(track_id, "spam", &mut tx);
+    lyrics_repository::set_hidden_tx(hidden_id, true, &mut tx).unwrap();
+    publish(track_id, "current", &mut tx);
+
+    let result = revert_lyrics_tx(track_id, hidden_id, "mod", "restore", false, &mut tx);
+    assert!(matches!(result, Err(ApiError::ConflictError(_))));
+    assert!(revert_lyrics_tx(track_id, hidden_id, "mod", "restore", true, &mut tx).is_ok());
+    tx.commit().unwrap();
+
+    assert_eq!(served_lyrics(track_id, &mut conn).as_deref(), Some("spam"));
+  }
+}
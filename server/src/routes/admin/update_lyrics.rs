@@ -0,0 +1,97 @@
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::{audit_log_repository, lyrics_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLyricsRequest {
+  hidden: bool,
+}
+
+// Hide or unhide a lyrics revision. Tracks never serve hidden revisions.
+pub async fn route(
+  admin: Admin,
+  Path(lyrics_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<UpdateLyricsRequest>,
+) -> Result<StatusCode, ApiError> {
+  let track_id = {
+    let mut conn = state.pool.get()?;
+    update_lyrics(lyrics_id, &payload, &admin, &mut conn)?
+  };
+
+  search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+
+  tracing::info!(message = "lyrics updated", lyrics_id = lyrics_id, hidden = payload.hidden, actor = admin.actor);
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn update_lyrics(lyrics_id: i64, payload: &UpdateLyricsRequest, admin: &Admin, conn: &mut Connection) -> Result<i64, ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+  if lyrics.taken_down && !payload.hidden {
+    return Err(ApiError::ValidationError("Lyrics that are taken down cannot be unhidden, lift the takedown first".to_owned()));
+  }
+  lyrics_repository::set_hidden_tx(lyrics_id, payload.hidden, &mut tx)?;
+
+  let updated_lyrics = lyrics_repository::get_lyrics_by_id_tx(lyrics_id, &mut tx)?;
+  audit_log_repository::add_one_tx(
+    &admin.actor,
+    if payload.hidden { AuditAction::HideLyrics } else { AuditAction::UnhideLyrics },
+    Some(lyrics.track_id),
+    Some(lyrics_id),
+    Some(&serde_json::to_value(&lyrics)?),
+    Some(&serde_json::to_value(&updated_lyrics)?),
+    &mut tx,
+  )?;
+
+  tx.commit()?;
+
+  Ok(lyrics.track_id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{routes::admin::tests::*, test_state};
+
+  #[tokio::test]
+  async fn hidden_lyrics_are_not_served_until_unhidden() {
+    let state = test_state(None);
+    let (track_id, first_id, second_id) = add_track(&state);
+
+    let result = route(admin(), Path(second_id), State(state.clone()), Json(UpdateLyricsRequest { hidden: true })).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+    assert_eq!(served_lyrics_id(track_id, &state), Some(first_id));
+
+    let result = route(admin(), Path(second_id), State(state.clone()), Json(UpdateLyricsRequest { hidden: false })).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+    assert_eq!(served_lyrics_id(track_id, &state), Some(second_id));
+  }
+
+  #[tokio::test]
+  async fn taken_down_lyrics_cannot_be_unhidden() {
+    let state = test_state(None);
+    let (track_id, first_id, second_id) = add_track(&state);
+    take_down(serde_json::json!({ "lyricsId": second_id, "reason": "DMCA" }), &state).await;
+
+    let result = route(admin(), Path(second_id), State(state.clone()), Json(UpdateLyricsRequest { hidden: false })).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    assert_eq!(served_lyrics_id(track_id, &state), Some(first_id));
+  }
+}
@@ -0,0 +1,139 @@
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use rusqlite::{Connection, ErrorCode};
+use serde::Deserialize;
+use std::sync::Arc;
+use validator::Validate;
+use crate::{
+  entities::audit_log::AuditAction,
+  errors::ApiError,
+  repositories::{audit_log_repository, track_repository},
+  routes::{admin::Admin, search_lyrics},
+  AppState,
+};
+
+#[derive(Validate, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTrackRequest {
+  #[validate(length(min = 1, max = 500, message = "must be between 1 and 500 characters"))]
+  track_name: Option<String>,
+  #[validate(length(min = 1, max = 500, message = "must be between 1 and 500 characters"))]
+  artist_name: Option<String>,
+  #[validate(length(min = 1, max = 500, message = "must be between 1 and 500 characters"))]
+  album_name: Option<String>,
+  #[validate(range(min = 1.0, max = 3600.0, message = "must be between 1 and 3600"))]
+  duration: Option<f64>,
+}
+
+// Edit track metadata. Fields that are not sent are left unchanged.
+pub async fn route(
+  admin: Admin,
+  Path(track_id): Path<i64>,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<UpdateTrackRequest>,
+) -> Result<StatusCode, ApiError> {
+  payload.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  {
+    let mut conn = state.pool.get()?;
+    update_track(track_id, &payload, &admin, &mut conn)?;
+  }
+
+  search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+
+  tracing::info!(message = "track updated", track_id = track_id, actor = admin.actor);
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn update_track(track_id: i64, payload: &UpdateTrackRequest, admin: &Admin, conn: &mut Connection) -> Result<(), ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let track = track_repository::get_track_tx(track_id, &mut tx)?
+    .ok_or(ApiError::TrackNotFoundError)?;
+
+  let result = track_repository::update_metadata_tx(
+    track_id,
+    payload.track_name.as_deref().map(|track_name| track_name.trim()),
+    payload.artist_name.as_deref().map(|artist_name| artist_name.trim()),
+    payload.album_name.as_deref().map(|album_name| album_name.trim()),
+    payload.duration,
+    &mut tx,
+  );
+
+  match result {
+    Ok(true) => {
+      let updated_track = track_repository::get_track_tx(track_id, &mut tx)?;
+      audit_log_repository::add_one_tx(
+        &admin.actor,
+        AuditAction::UpdateTrack,
+        Some(track_id),
+        None,
+        Some(&serde_json::to_value(&track)?),
+        Some(&serde_json::to_value(&updated_track)?),
+        &mut tx,
+      )?;
+      tx.commit()?;
+      Ok(())
+    },
+    Ok(false) => Err(ApiError::TrackNotFoundError),
+    Err(err) if is_constraint_violation(&err) => Err(ApiError::ValidationError(
+      "Another track already has this metadata, merge the tracks instead".to_owned()
+    )),
+    Err(err) => Err(err.into()),
+  }
+}
+
+fn is_constraint_violation(err: &anyhow::Error) -> bool {
+  err.downcast_ref::<rusqlite::Error>()
+    .and_then(|err| err.sqlite_error_code())
+    .is_some_and(|code| code == ErrorCode::ConstraintViolation)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{routes::admin::tests::*, test_state};
+
+  fn request(album_name: &str) -> Json<UpdateTrackRequest> {
+    Json(UpdateTrackRequest { track_name: None, artist_name: None, album_name: Some(album_name.to_owned()), duration: None })
+  }
+
+  #[tokio::test]
+  async fn only_sent_fields_are_changed() {
+    let state = test_state(None);
+    let (track_id, _, _) = add_track(&state);
+
+    let result = route(admin(), Path(track_id), State(state.clone()), request(" Other Album ")).await;
+    assert!(matches!(result, Ok(StatusCode::NO_CONTENT)));
+
+    let mut conn = state.pool.get().unwrap();
+    let track = track_repository::get_track(track_id, &mut conn).unwrap().unwrap();
+    assert_eq!(track.name.as_deref(), Some("Song"));
+    assert_eq!(track.album_name.as_deref(), Some("Other Album"));
+    assert_eq!(track.duration, Some(200.0));
+  }
+
+  #[tokio::test]
+  async fn metadata_of_another_track_is_rejected() {
+    let state = test_state(None);
+    let (track_id, _, _) = add_track(&state);
+    let other_track_id = {
+      let mut conn = state.pool.get().unwrap();
+      let mut tx = conn.transaction().unwrap();
+      let other_track_id = track_repository::add_one_tx("Song", "Artist", "Other Album", 200.0, &mut tx).unwrap();
+      tx.commit().unwrap();
+      other_track_id
+    };
+
+    let result = route(admin(), Path(other_track_id), State(state.clone()), request("Album")).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    let result = route(admin(), Path(track_id), State(state.clone()), request("")).await;
+    assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    let result = route(admin(), Path(0), State(state.clone()), request("Album")).await;
+    assert!(matches!(result, Err(ApiError::TrackNotFoundError)));
+  }
+}
@@ -0,0 +1,66 @@
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::{
+  convert::{self, SourceFormat, TargetFormat},
+  errors::ApiError,
+  lrc::lint,
+};
+use axum_macros::debug_handler;
+
+#[derive(Validate, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertRequest {
+  lyrics: String,
+  from: SourceFormat,
+  to: TargetFormat,
+  // Milliseconds added to every timestamp, applied after scaling
+  #[validate(range(min = -3600000, max = 3600000, message = "must be between -3600000 and 3600000"))]
+  shift: Option<i64>,
+  // Factor every timestamp is multiplied with
+  #[validate(range(exclusive_min = 0.0, max = 10.0, message = "must be greater than 0 and at most 10"))]
+  scale: Option<f64>,
+  // Track duration in seconds, used to end the last subtitle cue
+  #[validate(range(min = 1.0, max = 3600.0, message = "must be between 1 and 3600"))]
+  duration: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertResponse {
+  lyrics: String,
+}
+
+#[debug_handler]
+pub async fn route(Json(payload): Json<ConvertRequest>) -> Result<Json<ConvertResponse>, ApiError> {
+  payload.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  let mut lrc = convert::parse(&payload.lyrics, payload.from)
+    .map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  // Converting would silently drop these lines
+  let parse_errors = lint::parse_errors(&lrc);
+  if !parse_errors.is_empty() {
+    return Err(ApiError::LyricsValidationError(parse_errors));
+  }
+
+  if payload.to.needs_timestamps() && !lrc.has_timestamps() {
+    return Err(ApiError::ValidationError(
+      "lyrics do not contain any timestamp, they can only be converted to plain".to_owned(),
+    ));
+  }
+
+  lrc.bake_offset();
+
+  if let Some(scale) = payload.scale {
+    lrc.scale(scale);
+  }
+
+  if let Some(shift) = payload.shift {
+    lrc.shift(shift);
+  }
+
+  Ok(Json(ConvertResponse {
+    lyrics: convert::render(&lrc, payload.to, payload.duration),
+  }))
+}
@@ -9,15 +9,29 @@ use axum::{
 };
 use serde::Deserialize;
 use std::sync::Arc;
-use crate::{errors::ApiError, repositories::track_repository, AppState};
+use validator::Validate;
+use crate::{
+  entities::flag::FlagReason,
+  errors::ApiError,
+  repositories::track_repository,
+  routes::search_lyrics,
+  AppState,
+};
 use axum_macros::debug_handler;
 use crate::utils::is_valid_publish_token;
 
-#[derive(Deserialize, Debug)]
+#[derive(Validate, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct FlagLyricsRequest {
-    track_id: i64,
-    content: Option<String>,
+  track_id: i64,
+  // Older clients only send free-text content, which is filed as "other"
+  reason: Option<FlagReason>,
+  #[serde(alias = "content")]
+  #[validate(length(max = 1000, message = "must not be longer than 1000 characters"))]
+  details: Option<String>,
+  // Milliseconds, with the same meaning as the LRC [offset:] tag
+  #[validate(range(min = -3600000, max = 3600000, message = "must be between -3600000 and 3600000"))]
+  suggested_offset: Option<i64>,
 }
 
 #[debug_handler]
@@ -26,15 +40,41 @@ pub async fn route(
   State(state): State<Arc<AppState>>,
   Json(payload): Json<FlagLyricsRequest>,
 ) -> Result<StatusCode, ApiError> {
+  payload.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
+  if payload.suggested_offset.is_some() && payload.reason != Some(FlagReason::OutOfSync) {
+    return Err(ApiError::ValidationError("suggestedOffset: only allowed with the out_of_sync reason".to_owned()));
+  }
+
   match headers.get("X-Publish-Token") {
     Some(publish_token) => {
       let is_valid = is_valid_publish_token(publish_token.to_str()?, &state.challenge_cache).await;
 
       if is_valid {
-        let content = payload.content.unwrap_or("".to_string());
-        let track_id = payload.track_id;
-        let mut conn = state.pool.get()?;
-        track_repository::flag_track_last_lyrics(track_id, &content, &mut conn)?;
+        let token_prefix = publish_token.to_str()?.split(':').next().unwrap_or_default();
+        let reason = payload.reason.unwrap_or(FlagReason::Other);
+        let details = payload.details.as_deref().map(|details| details.trim()).filter(|details| !details.is_empty());
+        let is_auto_hidden = {
+          let mut conn = state.pool.get()?;
+          track_repository::flag_track_last_lyrics(
+            payload.track_id,
+            reason,
+            details,
+            payload.suggested_offset,
+            token_prefix,
+            state.auto_hide_policy,
+            &mut conn,
+          )?
+        };
+
+        let Some(is_auto_hidden) = is_auto_hidden else {
+          return Err(ApiError::LyricsNotFoundError);
+        };
+
+        if is_auto_hidden {
+          tracing::info!(message = "lyrics hidden after reaching the flag threshold", track_id = payload.track_id);
+          search_lyrics::invalidate_track(payload.track_id, &state.search_cache)?;
+        }
 
         Ok(StatusCode::CREATED)
       } else {
@@ -0,0 +1,53 @@
+use axum::{extract::{Path, State}, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use crate::{
+  entities::lyrics::Lyrics,
+  errors::ApiError,
+  repositories::{lyrics_repository::get_lyrics_by_id, track_repository::get_track},
+  AppState,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsResponse {
+  id: i64,
+  track_id: i64,
+  source: Option<String>,
+  created_at: Option<DateTime<Utc>>,
+  instrumental: bool,
+  score: i64,
+  plain_lyrics: Option<String>,
+  synced_lyrics: Option<String>,
+  enhanced_lyrics: Option<String>,
+}
+
+pub async fn route(Path(lyrics_id): Path<i64>, State(state): State<Arc<AppState>>) -> Result<Json<LyricsResponse>, ApiError> {
+  let mut conn = state.pool.get()?;
+  let lyrics = get_lyrics_by_id(lyrics_id, &mut conn)?.ok_or(ApiError::LyricsNotFoundError)?;
+
+  let is_track_taken_down = get_track(lyrics.track_id, &mut conn)?.is_some_and(|track| track.taken_down);
+  if lyrics.taken_down || is_track_taken_down {
+    return Err(ApiError::TakenDownError);
+  }
+  if lyrics.hidden {
+    return Err(ApiError::LyricsNotFoundError);
+  }
+
+  Ok(Json(create_response(lyrics)))
+}
+
+fn create_response(lyrics: Lyrics) -> LyricsResponse {
+  LyricsResponse {
+    id: lyrics.id,
+    track_id: lyrics.track_id,
+    source: lyrics.source,
+    created_at: lyrics.created_at,
+    instrumental: lyrics.instrumental,
+    score: lyrics.score,
+    plain_lyrics: lyrics.plain_lyrics,
+    synced_lyrics: lyrics.synced_lyrics,
+    enhanced_lyrics: lyrics.enhanced_lyrics,
+  }
+}
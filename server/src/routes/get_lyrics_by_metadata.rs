@@ -1,18 +1,23 @@
-use axum::{extract::{Query, State}, Json};
+use axum::{
+  extract::{Query, State},
+  http::header,
+  response::{IntoResponse, Response},
+  Json,
+};
 use rusqlite::Connection;
 use serde::{Deserialize,Serialize};
 use std::sync::Arc;
 use crate::{
     entities::{missing_track::MissingTrack, track::SimpleTrack},
     errors::ApiError,
-    repositories::track_repository::get_track_by_metadata,
+    repositories::{missing_track_repository, track_repository::get_track_by_metadata},
+    subtitles::{self, render_track, track_cues, Cue},
     utils::process_param,
     AppState,
 };
 use axum_macros::debug_handler;
 use validator::Validate;
 use anyhow::Result;
-use crossbeam_queue::ArrayQueue;
 
 #[derive(Clone, Validate, Deserialize)]
 pub struct QueryParams {
@@ -23,6 +28,12 @@ pub struct QueryParams {
   album_name: Option<String>,
   #[validate(range(min = 1.0, max = 3600.0, message = "must be between 1 and 3600"))]
   duration: Option<f64>,
+  format: Option<subtitles::Format>,
+  // Milliseconds, with the same meaning as the LRC [offset:] tag (positive values make lines appear sooner)
+  #[validate(range(min = -3600000, max = 3600000, message = "must be between -3600000 and 3600000"))]
+  offset: Option<i64>,
+  // Include the parsed synced lyrics as a list of timed lines
+  lines: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -37,10 +48,13 @@ pub struct TrackResponse {
   instrumental: bool,
   plain_lyrics: Option<String>,
   synced_lyrics: Option<String>,
+  enhanced_lyrics: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  lines: Option<Vec<Cue>>,
 }
 
 #[debug_handler]
-pub async fn route(Query(params): Query<QueryParams>, State(state): State<Arc<AppState>>) -> Result<Json<TrackResponse>, ApiError> {
+pub async fn route(Query(params): Query<QueryParams>, State(state): State<Arc<AppState>>) -> Result<Response, ApiError> {
   params.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
 
   // Process input parameters once
@@ -52,12 +66,27 @@ pub async fn route(Query(params): Query<QueryParams>, State(state): State<Arc<Ap
 
   if let (Some(track_name_lower), Some(artist_name_lower)) = (track_name_lower, artist_name_lower) {
     // Attempt to fetch the track with all provided metadata
-    if let Some(track) = fetch_track(&track_name_lower, &artist_name_lower, album_name_lower.as_deref(), params.duration, &mut conn).await? {
-      return Ok(Json(create_response(track)));
+    if let Some(mut track) = fetch_track(&track_name_lower, &artist_name_lower, album_name_lower.as_deref(), params.duration, &mut conn).await? {
+      // A taken down track is known, so it must not be sent to the missing track queue either
+      if track.taken_down {
+        return Err(ApiError::TakenDownError);
+      }
+
+      if let Some(offset) = params.offset {
+        track.last_lyrics = track.last_lyrics.map(|lyrics| lyrics.with_offset(offset));
+      }
+
+      return match params.format {
+        Some(format) => {
+          let subtitles = render_track(&track, format).ok_or(ApiError::SyncedLyricsNotFoundError)?;
+          Ok(([(header::CONTENT_TYPE, format.content_type())], subtitles).into_response())
+        },
+        None => Ok(Json(create_response(track, params.lines.unwrap_or(false))).into_response()),
+      };
     }
 
     // If not found, handle missing track logic
-    if let Err(e) = handle_missing_track(&params, &track_name_lower, &artist_name_lower, album_name_lower.as_deref(), &state).await {
+    if let Err(e) = handle_missing_track(&params, &track_name_lower, &artist_name_lower, album_name_lower.as_deref(), &state, &mut conn).await {
       tracing::error!(message = "failed to handle missing track", error = e.to_string());
     }
 
@@ -98,6 +127,7 @@ async fn handle_missing_track(
   artist_name_lower: &str,
   album_name_lower: Option<&str>,
   state: &Arc<AppState>,
+  conn: &mut Connection,
 ) -> Result<()> {
   if let (Some(album_name), Some(album_name_lower), Some(duration)) = (
     params.album_name.as_deref(),
@@ -114,14 +144,20 @@ async fn handle_missing_track(
     let cache_key = format!("missing_track:{}:{}:{}:{}", track_name_lower, artist_name_lower, album_name_lower, duration);
     if !state.get_cache.contains_key(&cache_key) {
       state.get_cache.insert(cache_key, "1".to_owned()).await;
-      send_to_queue(missing_track, &state.queue);
+      send_to_queue(missing_track, track_name_lower, artist_name_lower, album_name_lower, conn)?;
     }
   }
 
   Ok(())
 }
 
-fn create_response(track: SimpleTrack) -> TrackResponse {
+fn create_response(track: SimpleTrack, include_lines: bool) -> TrackResponse {
+  let lines = if include_lines {
+    Some(track_cues(&track).unwrap_or_default())
+  } else {
+    None
+  };
+
   let plain_lyrics = match track.last_lyrics {
     Some(ref lyrics) => lyrics.plain_lyrics.to_owned(),
     None => None
@@ -132,6 +168,11 @@ fn create_response(track: SimpleTrack) -> TrackResponse {
     None => None
   };
 
+  let enhanced_lyrics = match track.last_lyrics {
+    Some(ref lyrics) => lyrics.enhanced_lyrics.to_owned(),
+    None => None
+  };
+
   let instrumental = match track.last_lyrics {
     Some(ref lyrics) => lyrics.instrumental.to_owned(),
     None => false
@@ -147,24 +188,36 @@ fn create_response(track: SimpleTrack) -> TrackResponse {
     instrumental,
     plain_lyrics,
     synced_lyrics,
+    enhanced_lyrics,
+    lines,
   }
 }
 
-fn send_to_queue(missing_track: MissingTrack, queue: &ArrayQueue<MissingTrack>) {
-  match queue.push(missing_track.clone()) {
-    Ok(_) => tracing::debug!(
-      message = "sent missing track to queue",
-      track_name = missing_track.name,
-      artist_name = missing_track.artist_name,
-      album_name = missing_track.album_name,
-      duration = missing_track.duration,
-    ),
-    Err(missing_track) => tracing::debug!(
-      message = "failed to push to queue",
-      track_name = missing_track.name,
-      artist_name = missing_track.artist_name,
-      album_name = missing_track.album_name,
-      duration = missing_track.duration,
-    ),
-  }
+fn send_to_queue(
+  missing_track: MissingTrack,
+  track_name_lower: &str,
+  artist_name_lower: &str,
+  album_name_lower: &str,
+  conn: &mut Connection,
+) -> Result<()> {
+  missing_track_repository::add_one(
+    &missing_track.name,
+    &missing_track.artist_name,
+    &missing_track.album_name,
+    track_name_lower,
+    artist_name_lower,
+    album_name_lower,
+    missing_track.duration,
+    conn,
+  )?;
+
+  tracing::debug!(
+    message = "sent missing track to queue",
+    track_name = missing_track.name,
+    artist_name = missing_track.artist_name,
+    album_name = missing_track.album_name,
+    duration = missing_track.duration,
+  );
+
+  Ok(())
 }
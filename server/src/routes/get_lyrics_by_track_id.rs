@@ -1,7 +1,29 @@
-use axum::{extract::{Path, State}, Json};
-use serde::Serialize;
-use crate::{entities::track::SimpleTrack, errors::ApiError, repositories::track_repository::get_track_by_id, AppState};
+use axum::{
+  extract::{Path, Query, State},
+  http::header,
+  response::{IntoResponse, Response},
+  Json,
+};
+use serde::{Deserialize, Serialize};
+use crate::{
+  entities::track::SimpleTrack,
+  errors::ApiError,
+  repositories::track_repository::get_track_by_id,
+  subtitles::{self, render_track, track_cues, Cue},
+  AppState,
+};
 use std::sync::Arc;
+use validator::Validate;
+
+#[derive(Validate, Deserialize)]
+pub struct QueryParams {
+  format: Option<subtitles::Format>,
+  // Milliseconds, with the same meaning as the LRC [offset:] tag (positive values make lines appear sooner)
+  #[validate(range(min = -3600000, max = 3600000, message = "must be between -3600000 and 3600000"))]
+  offset: Option<i64>,
+  // Include the parsed synced lyrics as a list of timed lines
+  lines: Option<bool>,
+}
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,17 +37,37 @@ pub struct TrackResponse {
   instrumental: bool,
   plain_lyrics: Option<String>,
   synced_lyrics: Option<String>,
+  enhanced_lyrics: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  lines: Option<Vec<Cue>>,
 }
 
-pub async fn route(Path(track_id): Path<i64>, State(state): State<Arc<AppState>>) -> Result<Json<TrackResponse>, ApiError> {
+pub async fn route(
+  Path(track_id): Path<i64>,
+  Query(params): Query<QueryParams>,
+  State(state): State<Arc<AppState>>,
+) -> Result<Response, ApiError> {
+  params.validate().map_err(|e| ApiError::ValidationError(e.to_string()))?;
+
   let maybe_track = {
     let mut conn = state.pool.get()?;
     get_track_by_id(track_id, &mut conn)?
   };
 
   match maybe_track {
-    Some(track) => {
-      Ok(Json(create_response(track)))
+    Some(track) if track.taken_down => Err(ApiError::TakenDownError),
+    Some(mut track) => {
+      if let Some(offset) = params.offset {
+        track.last_lyrics = track.last_lyrics.map(|lyrics| lyrics.with_offset(offset));
+      }
+
+      match params.format {
+        Some(format) => {
+          let subtitles = render_track(&track, format).ok_or(ApiError::SyncedLyricsNotFoundError)?;
+          Ok(([(header::CONTENT_TYPE, format.content_type())], subtitles).into_response())
+        },
+        None => Ok(Json(create_response(track, params.lines.unwrap_or(false))).into_response()),
+      }
     }
     None => {
       Err(ApiError::TrackNotFoundError)
@@ -33,7 +75,13 @@ pub async fn route(Path(track_id): Path<i64>, State(state): State<Arc<AppState>>
   }
 }
 
-fn create_response(track: SimpleTrack) -> TrackResponse {
+fn create_response(track: SimpleTrack, include_lines: bool) -> TrackResponse {
+  let lines = if include_lines {
+    Some(track_cues(&track).unwrap_or_default())
+  } else {
+    None
+  };
+
   let plain_lyrics = match track.last_lyrics {
     Some(ref lyrics) => lyrics.plain_lyrics.to_owned(),
     None => None
@@ -44,6 +92,11 @@ fn create_response(track: SimpleTrack) -> TrackResponse {
     None => None
   };
 
+  let enhanced_lyrics = match track.last_lyrics {
+    Some(ref lyrics) => lyrics.enhanced_lyrics.to_owned(),
+    None => None
+  };
+
   let instrumental = match track.last_lyrics {
     Some(ref lyrics) => lyrics.instrumental.to_owned(),
     None => false
@@ -59,5 +112,7 @@ fn create_response(track: SimpleTrack) -> TrackResponse {
     instrumental,
     plain_lyrics,
     synced_lyrics,
+    enhanced_lyrics,
+    lines,
   }
 }
@@ -0,0 +1,81 @@
+use axum::{extract::{Path, Query, State}, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::{
+  entities::lyrics::Lyrics,
+  errors::ApiError,
+  lrc::diff::{diff, revision_diff_lines, ChangeKind, LineChange},
+  repositories::{lyrics_repository::get_lyrics_by_id, track_repository::get_track},
+  AppState,
+};
+
+#[derive(Deserialize)]
+pub struct QueryParams {
+  from: i64,
+  to: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResponse {
+  track_id: i64,
+  from_lyrics_id: i64,
+  to_lyrics_id: i64,
+  // Whether timestamps were compared, i.e. both revisions have synced lyrics
+  synced: bool,
+  instrumental_changed: bool,
+  added_count: usize,
+  removed_count: usize,
+  retimed_count: usize,
+  lines: Vec<LineChange>,
+}
+
+pub async fn route(
+  Path(track_id): Path<i64>,
+  Query(params): Query<QueryParams>,
+  State(state): State<Arc<AppState>>,
+) -> Result<Json<DiffResponse>, ApiError> {
+  let (track, from_lyrics, to_lyrics) = {
+    let mut conn = state.pool.get()?;
+    (
+      get_track(track_id, &mut conn)?,
+      get_lyrics_by_id(params.from, &mut conn)?,
+      get_lyrics_by_id(params.to, &mut conn)?,
+    )
+  };
+
+  match (track, from_lyrics, to_lyrics) {
+    (Some(track), Some(from_lyrics), Some(to_lyrics))
+      if from_lyrics.track_id == track_id && to_lyrics.track_id == track_id => {
+      if track.taken_down || from_lyrics.taken_down || to_lyrics.taken_down {
+        return Err(ApiError::TakenDownError);
+      }
+      if from_lyrics.hidden || to_lyrics.hidden {
+        return Err(ApiError::LyricsNotFoundError);
+      }
+      Ok(Json(create_response(track_id, &from_lyrics, &to_lyrics)))
+    },
+    _ => Err(ApiError::LyricsNotFoundError),
+  }
+}
+
+fn create_response(track_id: i64, from_lyrics: &Lyrics, to_lyrics: &Lyrics) -> DiffResponse {
+  let synced = from_lyrics.synced_lyrics.is_some() && to_lyrics.synced_lyrics.is_some();
+  let lines = diff(
+    &revision_diff_lines(from_lyrics.plain_lyrics.as_deref(), from_lyrics.synced_lyrics.as_deref(), synced),
+    &revision_diff_lines(to_lyrics.plain_lyrics.as_deref(), to_lyrics.synced_lyrics.as_deref(), synced),
+  );
+  let count = |kind: ChangeKind| lines.iter().filter(|line| line.kind == kind).count();
+
+  DiffResponse {
+    track_id,
+    from_lyrics_id: from_lyrics.id,
+    to_lyrics_id: to_lyrics.id,
+    synced,
+    instrumental_changed: from_lyrics.instrumental != to_lyrics.instrumental,
+    added_count: count(ChangeKind::Added),
+    removed_count: count(ChangeKind::Removed),
+    retimed_count: count(ChangeKind::Retimed),
+    lines,
+  }
+}
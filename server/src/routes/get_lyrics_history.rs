@@ -0,0 +1,56 @@
+use axum::{extract::{Path, State}, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use crate::{
+  entities::lyrics::Lyrics,
+  errors::ApiError,
+  repositories::{lyrics_repository, track_repository},
+  AppState,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsRevisionResponse {
+  id: i64,
+  source: Option<String>,
+  created_at: Option<DateTime<Utc>>,
+  has_plain_lyrics: bool,
+  has_synced_lyrics: bool,
+  instrumental: bool,
+  score: i64,
+  hidden: bool,
+  taken_down: bool,
+  current: bool,
+}
+
+pub async fn route(Path(track_id): Path<i64>, State(state): State<Arc<AppState>>) -> Result<Json<Vec<LyricsRevisionResponse>>, ApiError> {
+  let mut conn = state.pool.get()?;
+
+  let track = track_repository::get_track(track_id, &mut conn)?
+    .ok_or(ApiError::TrackNotFoundError)?;
+  if track.taken_down {
+    return Err(ApiError::TakenDownError);
+  }
+  let last_lyrics_id = track.last_lyrics_id;
+  let revisions = lyrics_repository::get_lyrics_by_track_id(track_id, &mut conn)?;
+
+  Ok(Json(create_response(revisions, last_lyrics_id)))
+}
+
+fn create_response(revisions: Vec<Lyrics>, last_lyrics_id: Option<i64>) -> Vec<LyricsRevisionResponse> {
+  revisions.into_iter().map(
+    |lyrics| LyricsRevisionResponse {
+      id: lyrics.id,
+      source: lyrics.source,
+      created_at: lyrics.created_at,
+      has_plain_lyrics: lyrics.has_plain_lyrics,
+      has_synced_lyrics: lyrics.has_synced_lyrics,
+      instrumental: lyrics.instrumental,
+      score: lyrics.score,
+      hidden: lyrics.hidden,
+      taken_down: lyrics.taken_down,
+      current: last_lyrics_id == Some(lyrics.id),
+    }
+  ).collect()
+}
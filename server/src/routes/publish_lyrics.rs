@@ -13,21 +13,31 @@ use std::sync::Arc;
 use crate::{
   errors::ApiError,
   repositories::{lyrics_repository, track_repository},
-  utils::{strip_timestamp, is_valid_publish_token},
+  utils::is_valid_publish_token,
+  lrc::{self, lint::{lint, LintIssue, LintReport, TrackMetadata, METADATA_TAGS}},
+  convert::{self, SourceFormat},
   AppState
 };
 use axum_macros::debug_handler;
-use regex::Regex;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PublishRequest {
-    track_name: String,
-    artist_name: String,
-    album_name: String,
-    duration: f64,
-    plain_lyrics: Option<String>,
-    synced_lyrics: Option<String>,
+    pub track_name: String,
+    pub artist_name: String,
+    pub album_name: String,
+    pub duration: f64,
+    pub plain_lyrics: Option<String>,
+    pub synced_lyrics: Option<String>,
+    pub synced_format: Option<SourceFormat>,
+}
+
+pub struct PreparedLyrics {
+  pub plain_lyrics: Option<String>,
+  pub synced_lyrics: Option<String>,
+  pub enhanced_lyrics: Option<String>,
+  pub instrumental: bool,
+  pub report: LintReport,
 }
 
 #[debug_handler]
@@ -41,9 +51,15 @@ pub async fn route(
       let is_valid = is_valid_publish_token(publish_token.to_str()?, &state.challenge_cache).await;
 
       if is_valid {
+        let mut lyrics = prepare_lyrics(&payload);
+
+        if !lyrics.report.errors.is_empty() {
+          return Err(ApiError::LyricsValidationError(std::mem::take(&mut lyrics.report.errors)));
+        }
+
         {
           let mut conn = state.pool.get()?;
-          publish_lyrics(&payload, &mut conn)?;
+          publish_lyrics(&payload, &lyrics, &mut conn)?;
         }
 
         Ok(StatusCode::CREATED)
@@ -55,60 +71,139 @@ pub async fn route(
   }
 }
 
-fn publish_lyrics(payload: &PublishRequest, conn: &mut Connection) -> Result<()> {
+// Turn the submitted lyrics into what will be stored, linting them along the way
+pub fn prepare_lyrics(payload: &PublishRequest) -> PreparedLyrics {
+  let mut plain_lyrics = payload.plain_lyrics.as_ref().filter(|s| !s.is_empty()).map(|s| s.to_owned());
+  let mut synced_lyrics = payload.synced_lyrics.as_ref().filter(|s| !s.is_empty()).map(|s| s.to_owned());
+  let mut enhanced_lyrics = None;
+
+  // Convert subtitle uploads to canonical LRC
+  let synced_format = payload.synced_format.filter(|format| !format.is_lrc());
+  if let (Some(lyrics), Some(format)) = (synced_lyrics.as_ref(), synced_format) {
+    match convert::parse(lyrics, format) {
+      Ok(converted_lyrics) => synced_lyrics = Some(converted_lyrics.to_string()),
+      Err(err) => {
+        let mut report = LintReport::default();
+        report.errors.push(LintIssue {
+          line_number: Some(err.line_number),
+          code: "malformed_subtitles",
+          reason: err.reason,
+        });
+        return PreparedLyrics {
+          plain_lyrics,
+          synced_lyrics: None,
+          enhanced_lyrics: None,
+          instrumental: false,
+          report,
+        };
+      },
+    }
+  }
+
+  let mut parsed_synced_lyrics = synced_lyrics.as_deref().map(lrc::parse);
+
+  // Bake the [offset:] tag into the timestamps, since most players ignore it
+  if let Some(ref mut parsed_synced_lyrics) = parsed_synced_lyrics {
+    if parsed_synced_lyrics.tag("offset").is_some() {
+      parsed_synced_lyrics.bake_offset();
+      synced_lyrics = Some(parsed_synced_lyrics.to_string());
+    }
+  }
+
+  let metadata = TrackMetadata {
+    track_name: payload.track_name.trim(),
+    artist_name: payload.artist_name.trim(),
+    album_name: payload.album_name.trim(),
+    duration: payload.duration,
+  };
+
+  let report = match parsed_synced_lyrics {
+    Some(ref parsed_synced_lyrics) => lint(parsed_synced_lyrics, &metadata),
+    None => LintReport::default(),
+  };
+
+  // Tags that repeat the track metadata are redundant once the lyrics are stored
+  if let Some(ref mut parsed_synced_lyrics) = parsed_synced_lyrics {
+    if METADATA_TAGS.iter().any(|key| parsed_synced_lyrics.tag(key).is_some()) {
+      parsed_synced_lyrics.remove_tags(&METADATA_TAGS);
+      synced_lyrics = Some(parsed_synced_lyrics.to_string());
+    }
+  }
+
+  // Generate plain_lyrics from synced_lyrics
+  if plain_lyrics.is_none() {
+    plain_lyrics = parsed_synced_lyrics.as_ref().map(|lrc| lrc.to_plain());
+  }
+
+  // Keep enhanced LRC as is and store a line-level version for older clients
+  if let Some(ref parsed_synced_lyrics) = parsed_synced_lyrics {
+    if parsed_synced_lyrics.is_enhanced() {
+      enhanced_lyrics = synced_lyrics.take();
+      synced_lyrics = Some(parsed_synced_lyrics.to_line_level().to_string());
+    }
+  }
+
+  // Detect the "[au: instrumental]" tag
+  let is_instrumental = parsed_synced_lyrics.as_ref().is_some_and(|lrc| lrc.is_instrumental());
+
+  if is_instrumental {
+    PreparedLyrics {
+      plain_lyrics: None,
+      synced_lyrics: None,
+      enhanced_lyrics: None,
+      instrumental: true,
+      report,
+    }
+  } else {
+    PreparedLyrics {
+      plain_lyrics,
+      synced_lyrics,
+      enhanced_lyrics,
+      instrumental: false,
+      report,
+    }
+  }
+}
+
+pub fn publish_lyrics(payload: &PublishRequest, lyrics: &PreparedLyrics, conn: &mut Connection) -> Result<(), ApiError> {
   let mut tx = conn.transaction()?;
 
   let existing_track = track_repository::get_track_id_by_metadata_tx(
-    &payload.track_name.trim(),
-    &payload.artist_name.trim(),
-    &payload.album_name.trim(),
+    payload.track_name.trim(),
+    payload.artist_name.trim(),
+    payload.album_name.trim(),
     payload.duration,
     &mut tx,
   )?;
 
   let track_id = match existing_track {
-    Some(track_id) => track_id,
+    Some(track_id) => {
+      let is_taken_down = track_repository::get_track_tx(track_id, &mut tx)?.is_some_and(|track| track.taken_down);
+      if is_taken_down {
+        return Err(ApiError::TakenDownError);
+      }
+      track_id
+    },
     None => track_repository::add_one_tx(
-      &payload.track_name.trim(),
-      &payload.artist_name.trim(),
-      &payload.album_name.trim(),
+      payload.track_name.trim(),
+      payload.artist_name.trim(),
+      payload.album_name.trim(),
       payload.duration,
       &mut tx,
     )?
   };
 
-  let mut plain_lyrics = payload.plain_lyrics.as_ref().filter(|s| !s.is_empty()).map(|s| s.to_owned());
-  let synced_lyrics = payload.synced_lyrics.as_ref().filter(|s| !s.is_empty()).map(|s| s.to_owned());
-
-  // Generate plain_lyrics from synced_lyrics
-  if plain_lyrics.is_none() && synced_lyrics.is_some() {
-    plain_lyrics = Some(strip_timestamp(synced_lyrics.as_deref().unwrap()));
-  }
-
-  // Create a regex to match "[au: instrumental]" or "[au:instrumental]"
-  let re = Regex::new(r"\[au:\s*instrumental\]").expect("Invalid regex");
-  let is_instrumental = synced_lyrics.as_ref().map_or(false, |lyrics| re.is_match(lyrics));
-
-  if is_instrumental {
-    // Mark the track as instrumental
-    lyrics_repository::add_one_tx(
-      &None,
-      &None,
-      track_id,
-      true,
-      &Some("lrclib".to_owned()),
-      &mut tx,
-    )?;
-  } else {
-    lyrics_repository::add_one_tx(
-      &plain_lyrics,
-      &synced_lyrics,
-      track_id,
-      false,
-      &Some("lrclib".to_owned()),
-      &mut tx,
-    )?;
-  }
+  let score = lyrics_repository::get_next_top_score_tx(track_id, &mut tx)?;
+  let lyrics_id = lyrics_repository::add_one_tx(
+    &lyrics.plain_lyrics,
+    &lyrics.synced_lyrics,
+    &lyrics.enhanced_lyrics,
+    track_id,
+    lyrics.instrumental,
+    &Some("lrclib".to_owned()),
+    &mut tx,
+  )?;
+  lyrics_repository::set_score_tx(lyrics_id, score, &mut tx)?;
 
   tx.commit()?;
 
@@ -1,5 +1,7 @@
+use anyhow::Result;
 use axum::{extract::{Query, State}, Json};
 use chrono::{DateTime, Utc};
+use moka::future::Cache;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use crate::{
@@ -104,6 +106,15 @@ pub async fn route(Query(params): Query<QueryParams>, State(state): State<Arc<Ap
   Ok(Json(response))
 }
 
+// Drop cached search results that contain the track, e.g. after its lyrics were changed by a moderator
+pub fn invalidate_track(track_id: i64, search_cache: &Cache<String, String>) -> Result<()> {
+  search_cache.invalidate_entries_if(move |_, cached_result_str| {
+    serde_json::from_str::<CachedResult>(cached_result_str)
+      .is_ok_and(|cached_result| cached_result.tracks.iter().any(|track| track.id == track_id))
+  })?;
+  Ok(())
+}
+
 fn create_response(tracks: Vec<SimpleTrack>) -> Vec<TrackResponse> {
   tracks.iter().map(
     |track| {
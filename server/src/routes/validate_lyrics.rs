@@ -0,0 +1,33 @@
+use axum::Json;
+use serde::Serialize;
+use crate::{
+  errors::ApiError,
+  lrc::lint::LintIssue,
+  routes::publish_lyrics::{prepare_lyrics, PublishRequest},
+};
+use axum_macros::debug_handler;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationResponse {
+  valid: bool,
+  errors: Vec<LintIssue>,
+  warnings: Vec<LintIssue>,
+  plain_lyrics: Option<String>,
+  instrumental: bool,
+  enhanced: bool,
+}
+
+#[debug_handler]
+pub async fn route(Json(payload): Json<PublishRequest>) -> Result<Json<ValidationResponse>, ApiError> {
+  let lyrics = prepare_lyrics(&payload);
+
+  Ok(Json(ValidationResponse {
+    valid: lyrics.report.errors.is_empty(),
+    errors: lyrics.report.errors,
+    warnings: lyrics.report.warnings,
+    plain_lyrics: lyrics.plain_lyrics,
+    instrumental: lyrics.instrumental,
+    enhanced: lyrics.enhanced_lyrics.is_some(),
+  }))
+}
@@ -0,0 +1,143 @@
+use axum::{
+  extract::State,
+  http::{
+    StatusCode,
+    HeaderMap,
+  },
+  Json,
+};
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::{
+  errors::ApiError,
+  repositories::{lyrics_repository, lyrics_vote_repository, track_repository},
+  routes::search_lyrics,
+  utils::is_valid_publish_token,
+  AppState,
+};
+use axum_macros::debug_handler;
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Vote {
+  Up,
+  Down,
+}
+
+impl Vote {
+  fn value(&self) -> i64 {
+    match self {
+      Vote::Up => 1,
+      Vote::Down => -1,
+    }
+  }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteLyricsRequest {
+  lyrics_id: i64,
+  vote: Vote,
+}
+
+#[debug_handler]
+pub async fn route(
+  headers: HeaderMap,
+  State(state): State<Arc<AppState>>,
+  Json(payload): Json<VoteLyricsRequest>,
+) -> Result<StatusCode, ApiError> {
+  match headers.get("X-Publish-Token") {
+    Some(publish_token) => {
+      let is_valid = is_valid_publish_token(publish_token.to_str()?, &state.challenge_cache).await;
+
+      if is_valid {
+        let (track_id, served_lyrics_changed) = {
+          let mut conn = state.pool.get()?;
+          vote_lyrics(&payload, &mut conn)?
+        };
+
+        // Cached search results still show the previously served revision
+        if served_lyrics_changed {
+          search_lyrics::invalidate_track(track_id, &state.search_cache)?;
+        }
+
+        Ok(StatusCode::CREATED)
+      } else {
+        Err(ApiError::IncorrectPublishTokenError)
+      }
+    },
+    None => Err(ApiError::IncorrectPublishTokenError)
+  }
+}
+
+// Record the vote and update the revision's score. Returns the track id and whether
+// the track now serves a different revision. Only revisions that are publicly visible can be voted on.
+fn vote_lyrics(payload: &VoteLyricsRequest, conn: &mut Connection) -> Result<(i64, bool), ApiError> {
+  let mut tx = conn.transaction()?;
+
+  let lyrics = lyrics_repository::get_lyrics_by_id_tx(payload.lyrics_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+  let track = track_repository::get_track_tx(lyrics.track_id, &mut tx)?
+    .ok_or(ApiError::LyricsNotFoundError)?;
+
+  if track.taken_down || lyrics.taken_down {
+    return Err(ApiError::TakenDownError);
+  }
+  if lyrics.hidden {
+    return Err(ApiError::LyricsNotFoundError);
+  }
+
+  let served_lyrics_id = track_repository::get_last_lyrics_id_tx(lyrics.track_id, &mut tx)?.flatten();
+
+  lyrics_vote_repository::add_one_tx(lyrics.id, payload.vote.value(), &mut tx)?;
+  lyrics_repository::add_score_tx(lyrics.id, payload.vote.value(), &mut tx)?;
+
+  let new_served_lyrics_id = track_repository::get_last_lyrics_id_tx(lyrics.track_id, &mut tx)?.flatten();
+
+  tx.commit()?;
+
+  Ok((lyrics.track_id, served_lyrics_id != new_served_lyrics_id))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::db::test_connection;
+
+  fn score(lyrics_id: i64, conn: &mut Connection) -> i64 {
+    conn.query_row("SELECT score FROM lyrics WHERE id = ?", [lyrics_id], |row| row.get(0)).unwrap()
+  }
+
+  #[test]
+  fn hidden_or_taken_down_lyrics_cannot_be_voted_on() {
+    let mut conn = test_connection();
+    let mut tx = conn.transaction().unwrap();
+    let track_id = track_repository::add_one_tx("Song", "Artist", "Album", 200.0, &mut tx).unwrap();
+    let publish = |tx: &mut rusqlite::Transaction| {
+      lyrics_repository::add_one_tx(&Some("lyrics".to_owned()), &None, &None, track_id, false, &Some("lrclib".to_owned()), tx).unwrap()
+    };
+    let visible_id = publish(&mut tx);
+    let hidden_id = publish(&mut tx);
+    lyrics_repository::set_hidden_tx(hidden_id, true, &mut tx).unwrap();
+    let taken_down_id = publish(&mut tx);
+    lyrics_repository::set_taken_down_tx(taken_down_id, true, &mut tx).unwrap();
+    tx.commit().unwrap();
+
+    let vote = |lyrics_id: i64, conn: &mut Connection| vote_lyrics(&VoteLyricsRequest { lyrics_id, vote: Vote::Up }, conn);
+
+    assert!(matches!(vote(hidden_id, &mut conn), Err(ApiError::LyricsNotFoundError)));
+    assert!(matches!(vote(taken_down_id, &mut conn), Err(ApiError::TakenDownError)));
+    assert!(matches!(vote(0, &mut conn), Err(ApiError::LyricsNotFoundError)));
+    assert_eq!((score(hidden_id, &mut conn), score(taken_down_id, &mut conn)), (0, 0));
+
+    assert!(vote(visible_id, &mut conn).is_ok());
+    assert_eq!(score(visible_id, &mut conn), 1);
+
+    let mut tx = conn.transaction().unwrap();
+    track_repository::set_taken_down_tx(track_id, true, &mut tx).unwrap();
+    tx.commit().unwrap();
+    assert!(matches!(vote(visible_id, &mut conn), Err(ApiError::TakenDownError)));
+    assert_eq!(score(visible_id, &mut conn), 1);
+  }
+}
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{entities::track::SimpleTrack, lrc::{self, Line, Lrc, Word}};
+
+pub mod srt;
+pub mod vtt;
+pub mod ttml;
+pub mod ass;
+
+// How long the last cue stays on screen when the track duration is unknown
+const DEFAULT_LAST_CUE_MS: i64 = 5000;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+  Srt,
+  Vtt,
+  Ttml,
+  Ass,
+}
+
+impl Format {
+  pub fn content_type(&self) -> &'static str {
+    match self {
+      Format::Srt => "application/x-subrip; charset=utf-8",
+      Format::Vtt => "text/vtt; charset=utf-8",
+      Format::Ttml => "application/ttml+xml; charset=utf-8",
+      Format::Ass => "text/x-ssa; charset=utf-8",
+    }
+  }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Cue {
+  pub start_ms: i64,
+  pub end_ms: i64,
+  pub text: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub voice: Option<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub words: Vec<CueWord>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CueWord {
+  pub start_ms: i64,
+  pub end_ms: i64,
+  pub text: String,
+}
+
+#[derive(Error, Debug)]
+#[error("line {line_number}: {reason}")]
+pub struct ParseError {
+  pub line_number: usize,
+  pub reason: String,
+}
+
+impl ParseError {
+  pub fn new(line_number: usize, reason: &str) -> Self {
+    Self { line_number, reason: reason.to_owned() }
+  }
+}
+
+// Build cues from LRC lines. A cue ends when the next line starts, or at the end of
+// the track for the last line. Lines without text only mark the end of the previous cue.
+pub fn cues(lrc: &Lrc, duration: Option<f64>) -> Vec<Cue> {
+  let timed_lines = lrc.timed_lines();
+  let duration_ms = duration.map(|duration| (duration * 1000.0).round() as i64);
+  let mut cues = vec![];
+
+  for (index, timed_line) in timed_lines.iter().enumerate() {
+    let line = timed_line.line;
+
+    if line.text.is_empty() {
+      continue;
+    }
+
+    let start_ms = timed_line.start_ms;
+    let end_ms = match (timed_lines.get(index + 1), duration_ms) {
+      (Some(next_line), _) => next_line.start_ms,
+      (None, Some(duration_ms)) if duration_ms > start_ms => duration_ms,
+      _ => start_ms.saturating_add(DEFAULT_LAST_CUE_MS),
+    };
+
+    // Word timestamps are absolute, so move them along with the line instance
+    // (offset tag applied, or a repeated line with several timestamps)
+    let shift = start_ms - line.timestamps.first().copied().unwrap_or(0);
+    let words = line.words
+      .iter()
+      .enumerate()
+      .filter(|(_, word)| !word.text.trim().is_empty())
+      .map(|(word_index, word)| CueWord {
+        start_ms: word.start_ms.saturating_add(shift).clamp(start_ms, end_ms),
+        end_ms: line.words
+          .get(word_index + 1)
+          .map_or(end_ms, |next_word| next_word.start_ms.saturating_add(shift))
+          .clamp(start_ms, end_ms),
+        text: word.text.to_owned(),
+      })
+      .collect();
+
+    cues.push(Cue {
+      start_ms,
+      end_ms,
+      text: line.text.to_owned(),
+      voice: line.voice.to_owned(),
+      words,
+    });
+  }
+
+  cues
+}
+
+// Build LRC lines from cues. Gaps between cues, and the end of the last cue, are kept
+// as empty lines so that rendering the LRC again gives back the same cue timings.
+pub fn to_lrc(cues: &[Cue]) -> Lrc {
+  let mut cues = cues.to_vec();
+  cues.sort_by_key(|cue| cue.start_ms);
+
+  let mut lrc = Lrc::default();
+  // Enhanced LRC only knows numbered voices, so map speaker names to v1, v2...
+  let mut voices: Vec<String> = vec![];
+
+  for (index, cue) in cues.iter().enumerate() {
+    let voice = cue.voice.as_ref().map(|voice| {
+      let position = voices.iter().position(|known_voice| known_voice == voice).unwrap_or_else(|| {
+        voices.push(voice.to_owned());
+        voices.len() - 1
+      });
+      format!("v{}", position + 1)
+    });
+
+    let mut words: Vec<Word> = cue.words
+      .iter()
+      .map(|word| Word { start_ms: word.start_ms, text: word.text.to_owned() })
+      .collect();
+    if let Some(last_word) = cue.words.last() {
+      words.push(Word { start_ms: last_word.end_ms, text: "".to_owned() });
+    }
+
+    lrc.lines.push(Line {
+      line_number: lrc.lines.len() + 1,
+      timestamps: vec![cue.start_ms],
+      text: cue.text.to_owned(),
+      voice,
+      words,
+    });
+
+    let next_start_ms = cues.get(index + 1).map(|next_cue| next_cue.start_ms);
+    if next_start_ms.filter(|&next_start_ms| next_start_ms <= cue.end_ms).is_none() {
+      lrc.lines.push(Line {
+        line_number: lrc.lines.len() + 1,
+        timestamps: vec![cue.end_ms],
+        text: "".to_owned(),
+        voice: None,
+        words: vec![],
+      });
+    }
+  }
+
+  lrc
+}
+
+pub fn render(format: Format, cues: &[Cue]) -> String {
+  match format {
+    Format::Srt => srt::render(cues),
+    Format::Vtt => vtt::render(cues),
+    Format::Ttml => ttml::render(cues),
+    Format::Ass => ass::render(cues),
+  }
+}
+
+// Cues for the track's synced lyrics, preferring enhanced LRC for word-level timing.
+// Returns None if the track has no synced lyrics.
+pub fn track_cues(track: &SimpleTrack) -> Option<Vec<Cue>> {
+  let lyrics = track.last_lyrics.as_ref()?;
+  let synced_lyrics = lyrics.enhanced_lyrics.as_ref().or(lyrics.synced_lyrics.as_ref())?;
+  Some(cues(&lrc::parse(synced_lyrics), track.duration))
+}
+
+pub fn render_track(track: &SimpleTrack, format: Format) -> Option<String> {
+  track_cues(track).map(|cues| render(format, &cues))
+}
+
+// Split milliseconds into hours, minutes, seconds and milliseconds
+pub fn split_timestamp(ms: i64) -> (i64, i64, i64, i64) {
+  let ms = ms.max(0);
+  (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+// Collapse the lines of a multi-line cue into a single lyrics line
+pub fn join_cue_lines(lines: &[&str]) -> String {
+  lines
+    .iter()
+    .map(|line| line.trim())
+    .filter(|line| !line.is_empty())
+    .collect::<Vec<&str>>()
+    .join(" ")
+}
+
+pub fn escape_xml(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // A duet line with word timings, a plain line, and an empty line ending the last cue
+  pub const SAMPLE_LYRICS: &str = "[00:01.00]v1: <00:01.00>Hello <00:01.50>world\n[00:03.00]v2: Bye & <see> you\n[00:05.00]";
+
+  pub fn sample_cues() -> Vec<Cue> {
+    cues(&lrc::parse(SAMPLE_LYRICS), None)
+  }
+
+  #[test]
+  fn cues_end_when_the_next_line_starts() {
+    let cues = sample_cues();
+    assert_eq!(cues.len(), 2);
+    assert_eq!((cues[0].start_ms, cues[0].end_ms, cues[0].text.as_str()), (1000, 3000, "Hello world"));
+    assert_eq!(cues[0].voice.as_deref(), Some("v1"));
+    assert_eq!(
+      cues[0].words.iter().map(|word| (word.start_ms, word.end_ms, word.text.as_str())).collect::<Vec<_>>(),
+      [(1000, 1500, "Hello "), (1500, 3000, "world")],
+    );
+    assert_eq!((cues[1].start_ms, cues[1].end_ms), (3000, 5000));
+  }
+
+  #[test]
+  fn last_cue_ends_with_the_track() {
+    let lrc = lrc::parse("[00:01.00]Hello\n[00:02.00]World");
+    assert_eq!(cues(&lrc, Some(10.0))[1].end_ms, 10_000);
+    assert_eq!(cues(&lrc, None)[1].end_ms, 2000 + DEFAULT_LAST_CUE_MS);
+  }
+
+  #[test]
+  fn huge_timestamps_saturate() {
+    let lrc = lrc::parse("[offset:-9223372036854775807]\n[00:01.00]<00:01.00>Hello <00:01.50>world");
+    let cues = cues(&lrc, None);
+    assert_eq!((cues[0].start_ms, cues[0].end_ms), (i64::MAX, i64::MAX));
+    assert_eq!(cues[0].words[1].end_ms, i64::MAX);
+  }
+
+  #[test]
+  fn repeated_line_moves_its_word_timings() {
+    let lrc = lrc::parse("[00:01.00][00:11.00]<00:01.00>La <00:01.50>la\n[00:05.00]Verse\n[00:15.00]");
+    let cues = cues(&lrc, None);
+    assert_eq!(
+      cues[2].words.iter().map(|word| (word.start_ms, word.end_ms)).collect::<Vec<_>>(),
+      [(11_000, 11_500), (11_500, 15_000)],
+    );
+  }
+
+  #[test]
+  fn cues_round_trip_through_lrc() {
+    let cues = sample_cues();
+    let lrc = to_lrc(&cues);
+    assert_eq!(
+      lrc.to_string(),
+      "[00:01.00]v1: <00:01.00>Hello <00:01.50>world<00:03.00>\n[00:03.00]v2: Bye & <see> you\n[00:05.00]",
+    );
+    assert_eq!(super::cues(&lrc, None), cues);
+  }
+
+  #[test]
+  fn gap_between_cues_is_kept_as_an_empty_line() {
+    let cues = vec![
+      Cue { start_ms: 1000, end_ms: 2000, text: "a".to_owned(), voice: None, words: vec![] },
+      Cue { start_ms: 4000, end_ms: 5000, text: "b".to_owned(), voice: None, words: vec![] },
+    ];
+    assert_eq!(to_lrc(&cues).to_string(), "[00:01.00]a\n[00:02.00]\n[00:04.00]b\n[00:05.00]");
+  }
+}
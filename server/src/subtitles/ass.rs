@@ -0,0 +1,83 @@
+use super::{split_timestamp, Cue};
+
+pub fn format_timestamp(ms: i64) -> String {
+  let (hours, minutes, seconds, millis) = split_timestamp(ms);
+  format!("{}:{:02}:{:02}.{:02}", hours, minutes, seconds, millis / 10)
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace('{', "\\{")
+    .replace('}', "\\}")
+}
+
+pub fn render(cues: &[Cue]) -> String {
+  let mut output = concat!(
+    "[Script Info]\n",
+    "ScriptType: v4.00+\n",
+    "PlayResX: 1920\n",
+    "PlayResY: 1080\n",
+    "\n",
+    "[V4+ Styles]\n",
+    "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n",
+    "Style: Default,Arial,64,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,3,0,2,60,60,60,1\n",
+    "\n",
+    "[Events]\n",
+    "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+  ).to_owned();
+
+  for cue in cues {
+    // Word timings become karaoke tags, with durations in centiseconds
+    let text = if cue.words.is_empty() {
+      escape(&cue.text)
+    } else {
+      let mut text = String::new();
+      let lead_in = cue.words[0].start_ms - cue.start_ms;
+      if lead_in > 0 {
+        text.push_str(&format!("{{\\k{}}}", lead_in / 10));
+      }
+      for word in &cue.words {
+        text.push_str(&format!("{{\\k{}}}{}", (word.end_ms - word.start_ms) / 10, escape(&word.text)));
+      }
+      text.trim_end().to_owned()
+    };
+
+    output.push_str(&format!(
+      "Dialogue: 0,{},{},Default,{},0,0,0,,{}\n",
+      format_timestamp(cue.start_ms),
+      format_timestamp(cue.end_ms),
+      cue.voice.as_deref().unwrap_or(""),
+      text,
+    ));
+  }
+
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::subtitles::tests::sample_cues;
+
+  #[test]
+  fn word_timings_become_karaoke_tags() {
+    let output = render(&sample_cues());
+    let dialogues: Vec<&str> = output.lines().filter(|line| line.starts_with("Dialogue:")).collect();
+    assert_eq!(dialogues, [
+      "Dialogue: 0,0:00:01.00,0:00:03.00,Default,v1,0,0,0,,{\\k50}Hello {\\k150}world",
+      "Dialogue: 0,0:00:03.00,0:00:05.00,Default,v2,0,0,0,,Bye & <see> you",
+    ]);
+  }
+
+  #[test]
+  fn override_braces_are_escaped() {
+    let cues = [Cue { start_ms: 0, end_ms: 1000, text: "{\\b1}a\\b".to_owned(), voice: None, words: vec![] }];
+    assert!(render(&cues).ends_with(",,\\{\\\\b1\\}a\\\\b\n"));
+  }
+
+  #[test]
+  fn timestamps_have_centiseconds() {
+    assert_eq!(format_timestamp(3_723_456), "1:02:03.45");
+  }
+}
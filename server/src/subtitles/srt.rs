@@ -0,0 +1,130 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use super::{join_cue_lines, split_timestamp, Cue, ParseError};
+
+lazy_static! {
+  static ref TIMING_RE: Regex = Regex::new(r"^(\d+):(\d{1,2}):(\d{1,2})[,.](\d{1,3})\s*-->\s*(\d+):(\d{1,2}):(\d{1,2})[,.](\d{1,3})").unwrap();
+  // Only the formatting tags SRT players understand, so text like <3 is kept
+  static ref TAG_RE: Regex = Regex::new(r"(?i)</?(b|i|u|font)\b[^>]*>|\{\\[^}]*\}").unwrap();
+}
+
+pub fn format_timestamp(ms: i64) -> String {
+  let (hours, minutes, seconds, millis) = split_timestamp(ms);
+  format!("{:02}:{:02}:{:02},{:03}", hours, minutes, seconds, millis)
+}
+
+pub fn render(cues: &[Cue]) -> String {
+  cues
+    .iter()
+    .enumerate()
+    .map(|(index, cue)| {
+      format!(
+        "{}\n{} --> {}\n{}\n",
+        index + 1,
+        format_timestamp(cue.start_ms),
+        format_timestamp(cue.end_ms),
+        cue.text,
+      )
+    })
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub fn parse(input: &str) -> Result<Vec<Cue>, ParseError> {
+  let mut cues = vec![];
+  let lines: Vec<&str> = input.trim_start_matches('\u{feff}').lines().collect();
+  let mut index = 0;
+
+  while index < lines.len() {
+    if lines[index].trim().is_empty() {
+      index += 1;
+      continue;
+    }
+
+    // Skip the optional cue number
+    if !lines[index].contains("-->") {
+      index += 1;
+    }
+
+    let line_number = index + 1;
+    let Some(captures) = lines.get(index).and_then(|line| TIMING_RE.captures(line.trim())) else {
+      return Err(ParseError::new(line_number, "expected a timing line like 00:00:12,340 --> 00:00:15,000"));
+    };
+    let timestamp = |offset: usize| -> Option<i64> {
+      let part = |i: usize| captures[offset + i].parse::<i64>().ok();
+      let fraction = &captures[offset + 4];
+      part(1)?.checked_mul(3_600_000)?.checked_add(
+        part(2)? * 60_000 + part(3)? * 1000 + fraction.parse::<i64>().ok()? * 10_i64.pow(3 - fraction.len() as u32)
+      )
+    };
+    let (Some(start_ms), Some(end_ms)) = (timestamp(0), timestamp(4)) else {
+      return Err(ParseError::new(line_number, "timestamp is out of range"));
+    };
+
+    if end_ms < start_ms {
+      return Err(ParseError::new(line_number, "cue ends before it starts"));
+    }
+
+    index += 1;
+    let mut text_lines = vec![];
+    while index < lines.len() && !lines[index].trim().is_empty() {
+      text_lines.push(lines[index]);
+      index += 1;
+    }
+
+    let text = TAG_RE.replace_all(&join_cue_lines(&text_lines), "").trim().to_owned();
+
+    cues.push(Cue { start_ms, end_ms, text, voice: None, words: vec![] });
+  }
+
+  Ok(cues)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::subtitles::tests::sample_cues;
+
+  #[test]
+  fn cues_round_trip_without_voices_and_word_timings() {
+    let cues = sample_cues();
+    let output = render(&cues);
+    assert_eq!(output, "1\n00:00:01,000 --> 00:00:03,000\nHello world\n\n2\n00:00:03,000 --> 00:00:05,000\nBye & <see> you\n");
+
+    let parsed_cues = parse(&output).unwrap();
+    assert_eq!(parsed_cues.len(), 2);
+    for (parsed_cue, cue) in parsed_cues.iter().zip(&cues) {
+      assert_eq!((parsed_cue.start_ms, parsed_cue.end_ms), (cue.start_ms, cue.end_ms));
+      assert!(parsed_cue.voice.is_none());
+      assert!(parsed_cue.words.is_empty());
+    }
+    assert_eq!(parsed_cues[0].text, "Hello world");
+    assert_eq!(parsed_cues[1].text, "Bye & <see> you");
+  }
+
+  #[test]
+  fn formatting_and_line_breaks_are_dropped() {
+    let input = "\u{feff}1\r\n00:00:01.5 --> 00:00:02,250\r\n<i>Hello</i>\r\n{\\an8}world\r\n\r\n00:00:03,000 --> 00:00:04,000\r\nBye\r\n";
+    let cues = parse(input).unwrap();
+    assert_eq!((cues[0].start_ms, cues[0].end_ms, cues[0].text.as_str()), (1500, 2250, "Hello world"));
+    assert_eq!((cues[1].start_ms, cues[1].text.as_str()), (3000, "Bye"));
+  }
+
+  #[test]
+  fn text_that_looks_like_a_tag_is_kept() {
+    let input = "1\n00:00:01,000 --> 00:00:02,000\n<B><font color=\"red\">I <3 you</font></B> <intro> <u>x</U>\n";
+    assert_eq!(parse(input).unwrap()[0].text, "I <3 you <intro> x");
+  }
+
+  #[test]
+  fn malformed_timing_is_reported_with_its_line() {
+    let err = parse("1\n00:00:01,000 --> 00:00:02,000\na\n\n2\n00:00:03 --> 00:00:04\nb").unwrap_err();
+    assert_eq!(err.line_number, 6);
+
+    let err = parse("1\n00:00:02,000 --> 00:00:01,000\na").unwrap_err();
+    assert_eq!(err.reason, "cue ends before it starts");
+
+    let err = parse("1\n00:00:01,000 --> 9999999999999999:00:00,000\na").unwrap_err();
+    assert_eq!(err.reason, "timestamp is out of range");
+  }
+}
@@ -0,0 +1,245 @@
+use quick_xml::{events::{BytesStart, Event}, Reader};
+use super::{escape_xml, split_timestamp, Cue, CueWord, ParseError};
+
+pub fn format_timestamp(ms: i64) -> String {
+  let (hours, minutes, seconds, millis) = split_timestamp(ms);
+  format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+}
+
+pub fn render(cues: &[Cue]) -> String {
+  let mut output = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+    "<tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:ttm=\"http://www.w3.org/ns/ttml#metadata\">\n",
+    "  <body>\n",
+    "    <div>\n",
+  ).to_owned();
+
+  for cue in cues {
+    let agent = match cue.voice {
+      Some(ref voice) => format!(" ttm:agent=\"{}\"", escape_xml(voice)),
+      None => "".to_owned(),
+    };
+
+    // Word timings are written as Apple-style timed spans
+    let text = if cue.words.is_empty() {
+      escape_xml(&cue.text)
+    } else {
+      cue.words
+        .iter()
+        .map(|word| {
+          let trailing_space = if word.text.ends_with(char::is_whitespace) { " " } else { "" };
+          format!(
+            "<span begin=\"{}\" end=\"{}\">{}</span>{}",
+            format_timestamp(word.start_ms),
+            format_timestamp(word.end_ms),
+            escape_xml(word.text.trim()),
+            trailing_space,
+          )
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_owned()
+    };
+
+    output.push_str(&format!(
+      "      <p begin=\"{}\" end=\"{}\"{}>{}</p>\n",
+      format_timestamp(cue.start_ms),
+      format_timestamp(cue.end_ms),
+      agent,
+      text,
+    ));
+  }
+
+  output.push_str("    </div>\n  </body>\n</tt>\n");
+  output
+}
+
+// Parse a TTML time expression: clock time (00:01:02.500, 01:02.500) or offset time (62.5s, 62500ms)
+pub fn parse_time(input: &str) -> Option<i64> {
+  let input = input.trim();
+
+  let seconds_to_ms = |value: &str, scale: f64| -> Option<i64> {
+    value.parse::<f64>().ok().map(|value| (value * scale).round() as i64)
+  };
+
+  if let Some(value) = input.strip_suffix("ms") {
+    return seconds_to_ms(value, 1.0);
+  }
+  if let Some(value) = input.strip_suffix('h') {
+    return seconds_to_ms(value, 3_600_000.0);
+  }
+  if let Some(value) = input.strip_suffix('m') {
+    return seconds_to_ms(value, 60_000.0);
+  }
+  if let Some(value) = input.strip_suffix('s') {
+    return seconds_to_ms(value, 1000.0);
+  }
+
+  let parts = input
+    .split(':')
+    .map(|part| part.parse::<f64>().ok())
+    .collect::<Option<Vec<f64>>>()?;
+  let seconds = match parts.as_slice() {
+    [seconds] => *seconds,
+    [minutes, seconds] => minutes * 60.0 + seconds,
+    [hours, minutes, seconds] => hours * 3600.0 + minutes * 60.0 + seconds,
+    // Frames are assumed to be at 30 fps
+    [hours, minutes, seconds, frames] => hours * 3600.0 + minutes * 60.0 + seconds + frames / 30.0,
+    _ => return None,
+  };
+  Some((seconds * 1000.0).round() as i64)
+}
+
+struct Timing {
+  begin: Option<i64>,
+  end: Option<i64>,
+  agent: Option<String>,
+}
+
+fn read_timing(element: &BytesStart, line_number: usize) -> Result<Timing, ParseError> {
+  let mut begin = None;
+  let mut end = None;
+  let mut dur = None;
+  let mut agent = None;
+
+  for attribute in element.attributes() {
+    let attribute = attribute.map_err(|err| ParseError::new(line_number, &err.to_string()))?;
+    let value = attribute.unescape_value().map_err(|err| ParseError::new(line_number, &err.to_string()))?;
+    let parse = |value: &str| parse_time(value).ok_or_else(|| ParseError::new(line_number, &format!("malformed time expression {}", value)));
+
+    match attribute.key.local_name().as_ref() {
+      b"begin" => begin = Some(parse(&value)?),
+      b"end" => end = Some(parse(&value)?),
+      b"dur" => dur = Some(parse(&value)?),
+      b"agent" => agent = Some(value.trim().to_owned()),
+      _ => {},
+    }
+  }
+
+  if end.is_none() {
+    end = begin.zip(dur)
+      .map(|(begin, dur)| begin.checked_add(dur).ok_or_else(|| ParseError::new(line_number, "end time is out of range")))
+      .transpose()?;
+  }
+
+  Ok(Timing { begin, end, agent })
+}
+
+// Collapse whitespace from XML formatting, keeping a single trailing space between words
+fn normalize_whitespace(text: &str) -> String {
+  let collapsed = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+  if !collapsed.is_empty() && text.ends_with(char::is_whitespace) {
+    format!("{} ", collapsed)
+  } else {
+    collapsed
+  }
+}
+
+pub fn parse(input: &str) -> Result<Vec<Cue>, ParseError> {
+  let mut reader = Reader::from_str(input);
+  let mut cues = vec![];
+  let mut cue: Option<Cue> = None;
+  let mut cue_text = String::new();
+
+  loop {
+    let line_number = input[..reader.buffer_position().min(input.len())].matches('\n').count() + 1;
+    let event = reader.read_event().map_err(|err| ParseError::new(line_number, &err.to_string()))?;
+
+    match event {
+      Event::Start(element) => match element.local_name().as_ref() {
+        b"p" => {
+          let timing = read_timing(&element, line_number)?;
+          let (Some(start_ms), Some(end_ms)) = (timing.begin, timing.end) else {
+            return Err(ParseError::new(line_number, "paragraph is missing begin or end time"));
+          };
+          cue = Some(Cue { start_ms, end_ms, text: "".to_owned(), voice: timing.agent, words: vec![] });
+          cue_text.clear();
+        },
+        b"span" => {
+          if let Some(ref mut cue) = cue {
+            let timing = read_timing(&element, line_number)?;
+            if let Some(start_ms) = timing.begin {
+              cue.words.push(CueWord { start_ms, end_ms: timing.end.unwrap_or(cue.end_ms), text: "".to_owned() });
+            }
+          }
+        },
+        _ => {},
+      },
+      Event::Empty(element) if element.local_name().as_ref() == b"br" && cue.is_some() => {
+        cue_text.push(' ');
+      },
+      Event::Text(text) => {
+        if let Some(ref mut cue) = cue {
+          let text = text.unescape().map_err(|err| ParseError::new(line_number, &err.to_string()))?;
+          cue_text.push_str(&text);
+          // Text inside a timed span belongs to that word. Whitespace between spans
+          // goes to the previous word.
+          if let Some(word) = cue.words.last_mut() {
+            word.text.push_str(&text);
+          }
+        }
+      },
+      Event::End(element) if element.local_name().as_ref() == b"p" => {
+        if let Some(mut cue) = cue.take() {
+          cue.text = normalize_whitespace(&cue_text).trim().to_owned();
+          for word in &mut cue.words {
+            word.text = normalize_whitespace(&word.text);
+          }
+          cue.words.retain(|word| !word.text.trim().is_empty());
+          if let Some(last_word) = cue.words.last_mut() {
+            last_word.text = last_word.text.trim_end().to_owned();
+          }
+          cues.push(cue);
+        }
+      },
+      Event::Eof => break,
+      _ => {},
+    }
+  }
+
+  Ok(cues)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::subtitles::tests::sample_cues;
+
+  #[test]
+  fn cues_round_trip_with_agents_and_word_timings() {
+    let cues = sample_cues();
+    let output = render(&cues);
+    assert!(output.contains(concat!(
+      "<p begin=\"00:00:01.000\" end=\"00:00:03.000\" ttm:agent=\"v1\">",
+      "<span begin=\"00:00:01.000\" end=\"00:00:01.500\">Hello</span> ",
+      "<span begin=\"00:00:01.500\" end=\"00:00:03.000\">world</span></p>",
+    )));
+    assert!(output.contains("<p begin=\"00:00:03.000\" end=\"00:00:05.000\" ttm:agent=\"v2\">Bye &amp; &lt;see&gt; you</p>"));
+    assert_eq!(parse(&output).unwrap(), cues);
+  }
+
+  #[test]
+  fn offset_times_and_durations_are_understood() {
+    let input = concat!(
+      "<tt xmlns=\"http://www.w3.org/ns/ttml\"><body><div>\n",
+      "  <p begin=\"1.5s\" dur=\"1000ms\">Hello<br/>world</p>\n",
+      "  <p begin=\"00:00:03:15\" end=\"0.1m\">\n    Bye\n  </p>\n",
+      "</div></body></tt>",
+    );
+    let cues = parse(input).unwrap();
+    assert_eq!((cues[0].start_ms, cues[0].end_ms, cues[0].text.as_str()), (1500, 2500, "Hello world"));
+    assert_eq!((cues[1].start_ms, cues[1].end_ms, cues[1].text.as_str()), (3500, 6000, "Bye"));
+  }
+
+  #[test]
+  fn overflowing_duration_is_reported() {
+    let err = parse("<tt>\n<p begin=\"9223372036854775807ms\" dur=\"1ms\">Hello</p>\n</tt>").unwrap_err();
+    assert_eq!((err.line_number, err.reason.as_str()), (2, "end time is out of range"));
+  }
+
+  #[test]
+  fn paragraph_without_timing_is_reported_with_its_line() {
+    let err = parse("<tt>\n<body>\n<p>Hello</p>\n</body>\n</tt>").unwrap_err();
+    assert_eq!(err.line_number, 3);
+  }
+}
@@ -0,0 +1,208 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use super::{join_cue_lines, split_timestamp, Cue, CueWord, ParseError};
+
+lazy_static! {
+  static ref TIMING_RE: Regex = Regex::new(r"^((?:\d+:)?\d{1,2}:\d{1,2}\.\d{1,3})\s*-->\s*((?:\d+:)?\d{1,2}:\d{1,2}\.\d{1,3})").unwrap();
+  static ref VOICE_RE: Regex = Regex::new(r"<v(?:\.[^\s>]*)?\s+([^>]+)>").unwrap();
+  static ref INLINE_TIMESTAMP_RE: Regex = Regex::new(r"<((?:\d+:)?\d{1,2}:\d{1,2}\.\d{1,3})>").unwrap();
+  static ref TAG_RE: Regex = Regex::new(r"</?[a-zA-Z][^>]*>").unwrap();
+}
+
+pub fn format_timestamp(ms: i64) -> String {
+  let (hours, minutes, seconds, millis) = split_timestamp(ms);
+  format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, seconds, millis)
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+pub fn render(cues: &[Cue]) -> String {
+  let mut output = "WEBVTT\n".to_owned();
+
+  for cue in cues {
+    let voice = match cue.voice {
+      Some(ref voice) => format!("<v {}>", escape(voice)),
+      None => "".to_owned(),
+    };
+
+    // Word timings become WebVTT karaoke-style inline timestamps
+    let text = if cue.words.is_empty() {
+      escape(&cue.text)
+    } else {
+      cue.words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+          if index == 0 {
+            escape(&word.text)
+          } else {
+            format!("<{}>{}", format_timestamp(word.start_ms), escape(&word.text))
+          }
+        })
+        .collect::<String>()
+        .trim()
+        .to_owned()
+    };
+
+    output.push_str(&format!(
+      "\n{} --> {}\n{}{}\n",
+      format_timestamp(cue.start_ms),
+      format_timestamp(cue.end_ms),
+      voice,
+      text,
+    ));
+  }
+
+  output
+}
+
+pub fn parse_timestamp(input: &str) -> Option<i64> {
+  let (clock, millis) = input.trim().split_once('.')?;
+  let parts = clock
+    .split(':')
+    .map(|part| part.parse::<i64>().ok())
+    .collect::<Option<Vec<i64>>>()?;
+  // Out of range timestamps are malformed
+  let seconds = match parts.as_slice() {
+    [minutes, seconds] => minutes.checked_mul(60)?.checked_add(*seconds)?,
+    [hours, minutes, seconds] => hours.checked_mul(3600)?.checked_add(minutes.checked_mul(60)?)?.checked_add(*seconds)?,
+    _ => return None,
+  };
+  let fraction: i64 = millis.parse().ok()?;
+  seconds.checked_mul(1000)?.checked_add(fraction * 10_i64.pow(3 - millis.len().min(3) as u32))
+}
+
+pub fn parse(input: &str) -> Result<Vec<Cue>, ParseError> {
+  let input = input.trim_start_matches('\u{feff}');
+
+  if !input.starts_with("WEBVTT") {
+    return Err(ParseError::new(1, "WebVTT files must start with WEBVTT"));
+  }
+
+  let mut cues = vec![];
+  let lines: Vec<&str> = input.lines().collect();
+  // Skip the header block
+  let mut index = lines.iter().position(|line| line.trim().is_empty()).unwrap_or(lines.len());
+
+  while index < lines.len() {
+    if lines[index].trim().is_empty() {
+      index += 1;
+      continue;
+    }
+
+    let block_start = index;
+    while index < lines.len() && !lines[index].trim().is_empty() {
+      index += 1;
+    }
+    let block = &lines[block_start..index];
+
+    if block[0].starts_with("NOTE") || block[0].starts_with("STYLE") || block[0].starts_with("REGION") {
+      continue;
+    }
+
+    // The first line may be a cue identifier
+    let Some(timing_index) = block.iter().take(2).position(|line| line.contains("-->")) else {
+      return Err(ParseError::new(block_start + 1, "expected a timing line like 00:00:12.340 --> 00:00:15.000"));
+    };
+    let line_number = block_start + timing_index + 1;
+    let (start_ms, end_ms) = TIMING_RE
+      .captures(block[timing_index].trim())
+      .and_then(|captures| Some((parse_timestamp(&captures[1])?, parse_timestamp(&captures[2])?)))
+      .ok_or_else(|| ParseError::new(line_number, "malformed cue timing"))?;
+
+    if end_ms < start_ms {
+      return Err(ParseError::new(line_number, "cue ends before it starts"));
+    }
+
+    cues.push(parse_cue_text(&join_cue_lines(&block[timing_index + 1..]), start_ms, end_ms));
+  }
+
+  Ok(cues)
+}
+
+fn parse_cue_text(text: &str, start_ms: i64, end_ms: i64) -> Cue {
+  let voice = VOICE_RE.captures(text).map(|captures| captures[1].trim().to_owned());
+  let mut words = vec![];
+  let mut word_start_ms = start_ms;
+  let mut last_end = 0;
+
+  for captures in INLINE_TIMESTAMP_RE.captures_iter(text) {
+    let Some(timestamp) = parse_timestamp(&captures[1]) else {
+      continue;
+    };
+    let tag = captures.get(0).unwrap();
+    words.push(CueWord {
+      start_ms: word_start_ms,
+      end_ms: timestamp,
+      text: unescape(&TAG_RE.replace_all(&text[last_end..tag.start()], "")),
+    });
+    word_start_ms = timestamp;
+    last_end = tag.end();
+  }
+
+  if last_end > 0 {
+    words.push(CueWord {
+      start_ms: word_start_ms,
+      end_ms,
+      text: unescape(&TAG_RE.replace_all(&text[last_end..], "")),
+    });
+    words.retain(|word| !word.text.trim().is_empty());
+  }
+
+  let plain_text = INLINE_TIMESTAMP_RE.replace_all(text, "");
+  let plain_text = unescape(TAG_RE.replace_all(&plain_text, "").trim());
+
+  Cue { start_ms, end_ms, text: plain_text, voice, words }
+}
+
+fn unescape(text: &str) -> String {
+  text
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&nbsp;", " ")
+    .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::subtitles::tests::sample_cues;
+
+  #[test]
+  fn cues_round_trip_with_voices_and_word_timings() {
+    let cues = sample_cues();
+    let output = render(&cues);
+    assert_eq!(output, concat!(
+      "WEBVTT\n",
+      "\n00:00:01.000 --> 00:00:03.000\n<v v1>Hello <00:00:01.500>world\n",
+      "\n00:00:03.000 --> 00:00:05.000\n<v v2>Bye &amp; &lt;see&gt; you\n",
+    ));
+    assert_eq!(parse(&output).unwrap(), cues);
+  }
+
+  #[test]
+  fn header_notes_and_identifiers_are_skipped() {
+    let input = "WEBVTT - lyrics\nKind: captions\n\nNOTE made by hand\n\nintro\n01:02.500 --> 01:04.000 align:start\n<c.yellow>Hello</c>\n";
+    let cues = parse(input).unwrap();
+    assert_eq!(cues.len(), 1);
+    assert_eq!((cues[0].start_ms, cues[0].end_ms, cues[0].text.as_str()), (62_500, 64_000, "Hello"));
+  }
+
+  #[test]
+  fn header_is_required() {
+    assert_eq!(parse("00:01.000 --> 00:02.000\nHello").unwrap_err().line_number, 1);
+  }
+
+  #[test]
+  fn timestamps_may_omit_hours() {
+    assert_eq!(parse_timestamp("01:02.5"), Some(62_500));
+    assert_eq!(parse_timestamp("1:01:02.345"), Some(3_662_345));
+    assert_eq!(parse_timestamp("01:02"), None);
+    assert_eq!(parse_timestamp("9999999999999999:00:00.000"), None);
+  }
+}
@@ -5,7 +5,7 @@ use regex::Regex;
 use collapse::collapse;
 
 pub fn prepare_input(input: &str) -> String {
-  let mut prepared_input = lower_lay_string(&input);
+  let mut prepared_input = lower_lay_string(input);
 
   let re = Regex::new(r#"[`~!@#$%^&*()_|+\-=?;:",.<>\{\}\[\]\\\/]"#).unwrap();
   prepared_input = re.replace_all(&prepared_input, " ").to_string();
@@ -19,12 +19,6 @@ pub fn prepare_input(input: &str) -> String {
   prepared_input
 }
 
-pub fn strip_timestamp(synced_lyrics: &str) -> String {
-  let re = Regex::new(r"^\[(.*)\] *").unwrap();
-  let plain_lyrics = re.replace_all(synced_lyrics, "");
-  plain_lyrics.to_string()
-}
-
 // tokens
 
 pub async fn is_valid_publish_token(publish_token: &str, challenge_cache: &Cache<String, String>) -> bool {
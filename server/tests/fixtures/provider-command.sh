@@ -0,0 +1,25 @@
+#!/bin/sh
+# Stand-in for a provider command, its answer depends on the requested track name
+input=$(cat)
+
+case "$input" in
+  *'"trackName":"hit"'*)
+    printf '{"plainLyrics":"a","syncedLyrics":"[00:01.00]a","instrumental":false}'
+    ;;
+  *'"trackName":"missing"'*)
+    ;;
+  *'"trackName":"busy"'*)
+    echo "rate limited" >&2
+    exit 75
+    ;;
+  *'"trackName":"broken"'*)
+    echo "crashed" >&2
+    exit 1
+    ;;
+  *'"trackName":"slow"'*)
+    # The album name is the file the process id is written to
+    pid_file=$(printf '%s' "$input" | sed -n 's/.*"albumName":"\([^"]*\)".*/\1/p')
+    echo $$ > "$pid_file"
+    exec sleep 30
+    ;;
+esac
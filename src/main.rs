@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 use clap::{Parser, Subcommand};
-use server::serve;
+use server::{providers::ProvidersConfig, serve};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -35,6 +35,101 @@ enum Commands {
       default_value_t = 0
     )]
     workers_count: u8,
+
+    /// Token for the admin API. The admin API is disabled if not set.
+    #[arg(
+      long,
+      value_name = "ADMIN_TOKEN",
+      env = "LRCLIB_ADMIN_TOKEN"
+    )]
+    admin_token: Option<String>,
+
+    /// Hide lyrics once they are flagged by this many distinct publish tokens. 0 disables it.
+    #[arg(
+      long,
+      value_name = "FLAG_THRESHOLD",
+      env = "LRCLIB_FLAG_THRESHOLD",
+      default_value_t = 0
+    )]
+    flag_threshold: u32,
+
+    /// Only count flags filed within this many hours towards the flag threshold
+    #[arg(
+      long,
+      value_name = "HOURS",
+      env = "LRCLIB_FLAG_WINDOW_HOURS",
+      default_value_t = 24
+    )]
+    flag_window_hours: u32,
+
+    /// Comma-separated lyrics providers the queue tries in order to find missing tracks
+    #[arg(
+      long,
+      value_name = "PROVIDERS",
+      env = "LRCLIB_PROVIDERS",
+      value_delimiter = ',',
+      default_value = "noop"
+    )]
+    providers: Vec<String>,
+
+    /// Directory of .lrc and .txt files, laid out as <artist>/<album>/<title>.lrc, for the local provider
+    #[arg(
+      long,
+      value_name = "DIRECTORY",
+      env = "LRCLIB_LRC_DIRECTORY"
+    )]
+    lrc_directory: Option<PathBuf>,
+
+    /// Base URL of the LRCLIB-compatible server the upstream provider queries, e.g. https://lrclib.net
+    #[arg(
+      long,
+      value_name = "URL",
+      env = "LRCLIB_UPSTREAM_URL"
+    )]
+    upstream_url: Option<String>,
+
+    /// Source recorded for lyrics found upstream. Defaults to the host of the upstream URL.
+    #[arg(
+      long,
+      value_name = "NAME",
+      env = "LRCLIB_UPSTREAM_NAME"
+    )]
+    upstream_name: Option<String>,
+
+    /// Seconds to wait for the upstream server before giving up on a request
+    #[arg(
+      long,
+      value_name = "SECONDS",
+      env = "LRCLIB_UPSTREAM_TIMEOUT",
+      default_value_t = 10
+    )]
+    upstream_timeout: u64,
+
+    /// Maximum number of requests in flight to the upstream server
+    #[arg(
+      long,
+      value_name = "COUNT",
+      env = "LRCLIB_UPSTREAM_MAX_CONCURRENT_REQUESTS",
+      default_value_t = 4
+    )]
+    upstream_max_concurrent_requests: usize,
+
+    /// Executable the command provider runs for each missing track, with the track as JSON on stdin
+    #[arg(
+      long,
+      value_name = "FILE",
+      env = "LRCLIB_PROVIDER_COMMAND"
+    )]
+    provider_command: Option<PathBuf>,
+
+    /// Seconds the provider command may run before it is killed
+    #[arg(
+      long,
+      value_name = "SECONDS",
+      env = "LRCLIB_PROVIDER_COMMAND_TIMEOUT",
+      default_value_t = 30
+    )]
+    provider_command_timeout: u64,
   },
 }
 
@@ -44,8 +139,25 @@ async fn main() {
   let cli = Cli::parse();
 
   match &cli.command {
-    Some(Commands::Serve { port, database, workers_count }) => {
-      serve(port.to_owned(), database, workers_count.to_owned()).await;
+    Some(Commands::Serve { port, database, workers_count, admin_token, flag_threshold, flag_window_hours, providers, lrc_directory, upstream_url, upstream_name, upstream_timeout, upstream_max_concurrent_requests, provider_command, provider_command_timeout }) => {
+      serve(
+        port.to_owned(),
+        database,
+        workers_count.to_owned(),
+        admin_token.to_owned(),
+        flag_threshold.to_owned(),
+        flag_window_hours.to_owned(),
+        ProvidersConfig {
+          names: providers.to_owned(),
+          lrc_directory: lrc_directory.to_owned(),
+          upstream_url: upstream_url.to_owned(),
+          upstream_name: upstream_name.to_owned(),
+          upstream_timeout: Duration::from_secs(*upstream_timeout),
+          upstream_max_concurrent_requests: upstream_max_concurrent_requests.to_owned(),
+          command: provider_command.to_owned(),
+          command_timeout: Duration::from_secs(*provider_command_timeout),
+        },
+      ).await;
     },
     None => {}
   }